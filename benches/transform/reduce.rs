@@ -5,9 +5,11 @@ use criterion::{
     criterion_group, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
     SamplingMode, Throughput,
 };
+use futures::StreamExt;
 use indexmap::IndexMap;
+use vector::event::EventArray;
 use vector::transforms::reduce::{Reduce, ReduceConfig};
-use vector_core::transform::Transform;
+use vector_core::transform::MultiOutputTaskTransform;
 
 use crate::common::{consume, FixedLogStream};
 
@@ -57,14 +59,13 @@ fn reduce(c: &mut Criterion) {
             b.to_async(tokio::runtime::Runtime::new().unwrap())
                 .iter_batched(
                     || {
-                        let reduce = Transform::event_task(
+                        let reduce = Box::new(
                             Reduce::new(&param.reduce_config, &Default::default()).unwrap(),
-                        )
-                        .into_task();
-                        (Box::new(reduce), Box::pin(param.input.clone()))
+                        );
+                        (reduce, param.input.clone().map(EventArray::from).boxed())
                     },
                     |(reduce, input)| async {
-                        let output = reduce.transform_events(input);
+                        let output = reduce.transform(input);
                         consume(output)
                     },
                     BatchSize::SmallInput,
@@ -1,4 +1,8 @@
+use std::io;
+
 use bytes::{BufMut, BytesMut};
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
 use tokio_util::codec::Encoder;
 use vector_core::{config::DataType, event::Event, schema};
 
@@ -19,6 +23,25 @@ pub struct JsonSerializerConfig {
     pub metric_tag_values: MetricTagValues,
 }
 
+/// Options for the JSON serializer.
+#[crate::configurable_component]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonSerializerOptions {
+    /// Whether to use pretty JSON formatting.
+    ///
+    /// When disabled (the default), events are written compactly with no insignificant whitespace.
+    /// As pretty output spans multiple lines, it can't be combined with newline-delimited framing.
+    #[serde(default)]
+    pub pretty: bool,
+
+    /// Whether to escape non-ASCII characters.
+    ///
+    /// When enabled, every character outside of the ASCII range is written as a `\uXXXX` escape
+    /// sequence (using a surrogate pair where needed), producing output that is pure ASCII.
+    #[serde(default)]
+    pub escape_non_ascii: bool,
+}
+
 impl JsonSerializerConfig {
     /// Creates a new `JsonSerializerConfig`.
     pub const fn new(metric_tag_values: MetricTagValues) -> Self {
@@ -47,12 +70,30 @@ impl JsonSerializerConfig {
 #[derive(Debug, Clone)]
 pub struct JsonSerializer {
     metric_tag_values: MetricTagValues,
+    options: JsonSerializerOptions,
 }
 
 impl JsonSerializer {
     /// Creates a new `JsonSerializer`.
     pub const fn new(metric_tag_values: MetricTagValues) -> Self {
-        Self { metric_tag_values }
+        Self::with_options(
+            metric_tag_values,
+            JsonSerializerOptions {
+                pretty: false,
+                escape_non_ascii: false,
+            },
+        )
+    }
+
+    /// Creates a new `JsonSerializer` with the given formatting options.
+    pub const fn with_options(
+        metric_tag_values: MetricTagValues,
+        options: JsonSerializerOptions,
+    ) -> Self {
+        Self {
+            metric_tag_values,
+            options,
+        }
     }
 
     /// Encode event and represent it as JSON value.
@@ -64,6 +105,25 @@ impl JsonSerializer {
         }
         .map_err(|e| e.to_string().into())
     }
+
+    fn write<W, T>(&self, writer: W, value: &T) -> serde_json::Result<()>
+    where
+        W: io::Write,
+        T: Serialize + ?Sized,
+    {
+        match (self.options.pretty, self.options.escape_non_ascii) {
+            (false, false) => value.serialize(&mut serde_json::Serializer::new(writer)),
+            (true, false) => value.serialize(&mut serde_json::Serializer::pretty(writer)),
+            (false, true) => value.serialize(&mut serde_json::Serializer::with_formatter(
+                writer,
+                AsciiFormatter(CompactFormatter),
+            )),
+            (true, true) => value.serialize(&mut serde_json::Serializer::with_formatter(
+                writer,
+                AsciiFormatter(PrettyFormatter::new()),
+            )),
+        }
+    }
 }
 
 impl Encoder<Event> for JsonSerializer {
@@ -72,19 +132,116 @@ impl Encoder<Event> for JsonSerializer {
     fn encode(&mut self, event: Event, buffer: &mut BytesMut) -> Result<(), Self::Error> {
         let writer = buffer.writer();
         match event {
-            Event::Log(log) => serde_json::to_writer(writer, &log),
+            Event::Log(log) => self.write(writer, &log),
             Event::Metric(mut metric) => {
                 if self.metric_tag_values == MetricTagValues::Single {
                     metric.reduce_tags_to_single();
                 }
-                serde_json::to_writer(writer, &metric)
+                self.write(writer, &metric)
             }
-            Event::Trace(trace) => serde_json::to_writer(writer, &trace),
+            Event::Trace(trace) => self.write(writer, &trace),
         }
         .map_err(Into::into)
     }
 }
 
+/// A JSON formatter that escapes every non-ASCII character in string values and object keys,
+/// delegating all structural formatting to the wrapped formatter.
+struct AsciiFormatter<F>(F);
+
+impl<F: Formatter> Formatter for AsciiFormatter<F> {
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut start = 0;
+        for (index, c) in fragment.char_indices() {
+            if c.is_ascii() {
+                continue;
+            }
+            if start < index {
+                writer.write_all(fragment[start..index].as_bytes())?;
+            }
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                write!(writer, "\\u{:04x}", unit)?;
+            }
+            start = index + c.len_utf8();
+        }
+        writer.write_all(fragment[start..].as_bytes())
+    }
+
+    fn begin_array<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.end_object_key(writer)
+    }
+
+    fn begin_object_value<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.begin_object_value(writer)
+    }
+
+    fn end_object_value<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.0.end_object_value(writer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::{Bytes, BytesMut};
@@ -214,6 +371,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_json_log_pretty() {
+        let event = Event::Log(LogEvent::from(btreemap! {
+            "x" => Value::from("23"),
+            "a" => Value::from(vec![Value::from(1)]),
+        }));
+        let bytes = serialize_with_options(
+            JsonSerializerOptions {
+                pretty: true,
+                escape_non_ascii: false,
+            },
+            event,
+        );
+
+        assert_eq!(bytes, "{\n  \"a\": [\n    1\n  ],\n  \"x\": \"23\"\n}");
+    }
+
+    #[test]
+    fn serialize_json_log_escape_non_ascii() {
+        let event = Event::Log(LogEvent::from(btreemap! {
+            "café" => Value::from("naïve 🦀"),
+        }));
+        let bytes = serialize_with_options(
+            JsonSerializerOptions {
+                pretty: false,
+                escape_non_ascii: true,
+            },
+            event.clone(),
+        );
+
+        assert_eq!(bytes, r#"{"caf\u00e9":"na\u00efve \ud83e\udd80"}"#);
+
+        // The escaped output must still round-trip to the same value.
+        let unescaped = serialize(JsonSerializerConfig::default(), event);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(&unescaped).unwrap()
+        );
+    }
+
     fn metric2() -> Event {
         Event::Metric(
             Metric::new(
@@ -234,4 +431,12 @@ mod tests {
         config.build().encode(input, &mut buffer).unwrap();
         buffer.freeze()
     }
+
+    fn serialize_with_options(options: JsonSerializerOptions, input: Event) -> Bytes {
+        let mut buffer = BytesMut::new();
+        JsonSerializer::with_options(MetricTagValues::default(), options)
+            .encode(input, &mut buffer)
+            .unwrap();
+        buffer.freeze()
+    }
 }
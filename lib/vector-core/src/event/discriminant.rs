@@ -31,6 +31,12 @@ impl Discriminant {
             .collect();
         Self { values }
     }
+
+    /// The values of the discriminant fields, in the order the fields were given. Fields missing
+    /// from the event have no value.
+    pub fn values(&self) -> &[Option<Value>] {
+        &self.values
+    }
 }
 
 impl PartialEq for Discriminant {
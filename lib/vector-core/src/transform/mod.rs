@@ -26,6 +26,7 @@ pub enum Transform {
     Function(Box<dyn FunctionTransform>),
     Synchronous(Box<dyn SyncTransform>),
     Task(Box<dyn TaskTransform<EventArray>>),
+    MultiOutputTask(Box<dyn MultiOutputTaskTransform>),
 }
 
 impl Transform {
@@ -104,6 +105,18 @@ impl Transform {
         Transform::Task(Box::new(WrapEventTask(v)))
     }
 
+    /// Create a new task transform that can write to multiple outputs.
+    ///
+    /// Like [`Transform::synchronous`], the outputs must be known in advance and returned via
+    /// `TransformConfig::outputs`. Attempting to send to any output not registered in advance is
+    /// considered a bug and will cause a panic.
+    ///
+    /// **Note:** You should prefer to implement [`FunctionTransform`] over this
+    /// where possible.
+    pub fn multi_output_task(v: impl MultiOutputTaskTransform + 'static) -> Self {
+        Transform::MultiOutputTask(Box::new(v))
+    }
+
     /// Mutably borrow the inner transform as a task transform.
     ///
     /// # Panics
@@ -176,6 +189,22 @@ pub trait TaskTransform<T: EventContainer + 'static>: Send + 'static {
     }
 }
 
+/// Broader than [`TaskTransform`], this trait allows task transforms to write to multiple
+/// outputs. Each item of the returned stream holds the events for every output, created with
+/// `TransformOutputsBuf::new_with_capacity` from the outputs returned via
+/// `TransformConfig::outputs`.
+///
+/// # Invariants
+///
+/// * It is an illegal invariant to implement `FunctionTransform` for a
+/// `MultiOutputTaskTransform` or vice versa.
+pub trait MultiOutputTaskTransform: Send + 'static {
+    fn transform(
+        self: Box<Self>,
+        task: Pin<Box<dyn Stream<Item = EventArray> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = TransformOutputsBuf> + Send>>;
+}
+
 /// Broader than the simple [`FunctionTransform`], this trait allows transforms to write to
 /// multiple outputs. Those outputs must be known in advanced and returned via
 /// `TransformConfig::outputs`. Attempting to send to any output not registered in advance is
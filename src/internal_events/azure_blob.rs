@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use metrics::counter;
+use vector_common::internal_event::{error_stage, error_type};
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct AzureBlobDeadLettered<'a> {
+    pub error: &'a azure_core::Error,
+    pub path: &'a Path,
+    pub count: usize,
+}
+
+impl InternalEvent for AzureBlobDeadLettered<'_> {
+    fn emit(self) {
+        error!(
+            message = "Upload permanently failed, wrote it to the dead-letter path.",
+            error = %self.error,
+            path = ?self.path,
+            count = self.count,
+            error_code = "dead_lettered",
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::SENDING,
+            internal_log_rate_limit = true,
+        );
+        // The events are rejected, so the sink driver already counts the error.
+    }
+}
+
+#[derive(Debug)]
+pub struct AzureBlobDeadLetterWriteError {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for AzureBlobDeadLetterWriteError {
+    fn emit(self) {
+        error!(
+            message = "Failed to write upload to the dead-letter path.",
+            error = %self.error,
+            error_code = "dead_letter_write_failed",
+            error_type = error_type::WRITER_FAILED,
+            stage = error_stage::SENDING,
+            internal_log_rate_limit = true,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_code" => "dead_letter_write_failed",
+            "error_type" => error_type::WRITER_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use metrics::{counter, gauge};
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
 use vector_core::{internal_event::InternalEvent, update_counter};
 
 use vector_common::internal_event::{error_stage, error_type};
@@ -152,6 +154,24 @@ impl InternalEvent for KafkaStatisticsReceived<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct KafkaHeadersTruncated<'a> {
+    pub header_field: &'a str,
+    pub dropped: usize,
+}
+
+impl InternalEvent for KafkaHeadersTruncated<'_> {
+    fn emit(self) {
+        warn!(
+            message = "Dropped headers exceeding the configured limits.",
+            header_field = self.header_field,
+            dropped = %self.dropped,
+            internal_log_rate_limit = true,
+        );
+        counter!("kafka_headers_dropped_total", self.dropped as u64);
+    }
+}
+
 pub struct KafkaHeaderExtractionError<'a> {
     pub header_field: &'a str,
 }
@@ -176,3 +196,47 @@ impl InternalEvent for KafkaHeaderExtractionError<'_> {
         counter!("kafka_header_extraction_failures_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct KafkaSendAcknowledged {
+    pub latency: Duration,
+    pub slow_send_threshold: Option<Duration>,
+}
+
+impl InternalEvent for KafkaSendAcknowledged {
+    fn emit(self) {
+        histogram!("kafka_send_latency_seconds", self.latency);
+        if let Some(threshold) = self.slow_send_threshold {
+            if self.latency > threshold {
+                debug!(
+                    message = "Kafka send was slow to be acknowledged.",
+                    latency_ms = %self.latency.as_millis(),
+                    threshold_ms = %threshold.as_millis(),
+                    internal_log_rate_limit = true,
+                );
+                counter!("kafka_slow_sends_total", 1);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KafkaMessageDelivered<'a> {
+    pub topic: &'a str,
+    pub partition: i32,
+}
+
+impl InternalEvent for KafkaMessageDelivered<'_> {
+    fn emit(self) {
+        trace!(
+            message = "Message delivered to Kafka.",
+            topic = %self.topic,
+            partition = %self.partition,
+        );
+        counter!(
+            "kafka_delivered_messages_total", 1,
+            "topic" => self.topic.to_string(),
+            "partition" => self.partition.to_string(),
+        );
+    }
+}
@@ -1,5 +1,10 @@
-use metrics::counter;
-use vector_core::internal_event::InternalEvent;
+use std::time::Duration;
+
+use crate::emit;
+use metrics::{counter, gauge};
+use vector_core::internal_event::{
+    error_stage, error_type, ComponentEventsDropped, InternalEvent, INTENTIONAL, UNINTENTIONAL,
+};
 
 #[derive(Debug)]
 pub struct ReduceStaleEventFlushed;
@@ -9,3 +14,148 @@ impl InternalEvent for ReduceStaleEventFlushed {
         counter!("stale_events_flushed_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct ReduceStateFlushed {
+    pub reason: &'static str,
+}
+
+impl InternalEvent for ReduceStateFlushed {
+    fn emit(self) {
+        counter!("reduce_states_flushed_total", 1, "reason" => self.reason);
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceStatesActive {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl InternalEvent for ReduceStatesActive {
+    fn emit(self) {
+        gauge!("reduce_states_active", self.count as f64);
+        gauge!("reduce_bytes_buffered", self.bytes as f64);
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceStateAge<'a> {
+    pub group: Option<&'a str>,
+    pub max_idle: Duration,
+    pub oldest: Duration,
+}
+
+impl InternalEvent for ReduceStateAge<'_> {
+    fn emit(self) {
+        let max_idle = self.max_idle.as_secs_f64();
+        let oldest = self.oldest.as_secs_f64();
+        match self.group {
+            Some(group) => {
+                gauge!("reduce_state_max_idle_seconds", max_idle, "group" => group.to_owned());
+                gauge!("reduce_oldest_state_age_seconds", oldest, "group" => group.to_owned());
+            }
+            None => {
+                gauge!("reduce_state_max_idle_seconds", max_idle);
+                gauge!("reduce_oldest_state_age_seconds", oldest);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceGroupCardinalityExceeded;
+
+impl InternalEvent for ReduceGroupCardinalityExceeded {
+    fn emit(self) {
+        debug!(
+            message = "Group cardinality limit reached, event was not reduced.",
+            internal_log_rate_limit = true
+        );
+        counter!("group_cardinality_exceeded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceLoneEndDropped;
+
+impl InternalEvent for ReduceLoneEndDropped {
+    fn emit(self) {
+        emit!(ComponentEventsDropped::<INTENTIONAL> {
+            count: 1,
+            reason: "Final event arrived with no open transaction.",
+        });
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceDuplicateDropped;
+
+impl InternalEvent for ReduceDuplicateDropped {
+    fn emit(self) {
+        emit!(ComponentEventsDropped::<INTENTIONAL> {
+            count: 1,
+            reason: "Duplicate event within combined event.",
+        });
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceFlushWebhookError {
+    pub error: String,
+}
+
+impl InternalEvent for ReduceFlushWebhookError {
+    fn emit(self) {
+        error!(
+            message = "Failed to send flush webhook.",
+            error = %self.error,
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::PROCESSING,
+            internal_log_rate_limit = true,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::REQUEST_FAILED,
+            "stage" => error_stage::PROCESSING,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceFlushWebhookDropped;
+
+impl InternalEvent for ReduceFlushWebhookDropped {
+    fn emit(self) {
+        debug!(
+            message = "Dropped flush webhook notification, too many requests in flight.",
+            internal_log_rate_limit = true,
+        );
+        counter!("reduce_flush_webhook_dropped_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceUnsupportedMetric {
+    pub value_type: &'static str,
+}
+
+impl InternalEvent for ReduceUnsupportedMetric {
+    fn emit(self) {
+        let reason = "Metric type is not supported by reduce.";
+        error!(
+            message = reason,
+            value_type = %self.value_type,
+            error_type = error_type::CONVERSION_FAILED,
+            stage = error_stage::PROCESSING,
+            internal_log_rate_limit = true
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::CONVERSION_FAILED,
+            "stage" => error_stage::PROCESSING,
+        );
+
+        emit!(ComponentEventsDropped::<UNINTENTIONAL> { count: 1, reason })
+    }
+}
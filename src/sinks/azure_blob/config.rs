@@ -1,7 +1,10 @@
-use std::{convert::TryInto, sync::Arc};
+use std::{convert::TryInto, path::PathBuf, sync::Arc};
 
 use azure_storage_blobs::prelude::*;
-use codecs::{encoding::Framer, JsonSerializerConfig, NewlineDelimitedEncoderConfig};
+use codecs::{
+    encoding::{Framer, SerializerConfig},
+    JsonSerializer, JsonSerializerConfig, JsonSerializerOptions, NewlineDelimitedEncoderConfig,
+};
 use tower::ServiceBuilder;
 use vector_common::sensitive_string::SensitiveString;
 use vector_config::configurable_component;
@@ -12,7 +15,10 @@ use crate::{
     config::{AcknowledgementsConfig, DataType, GenerateConfig, Input, SinkConfig, SinkContext},
     sinks::{
         azure_common::{
-            self, config::AzureBlobRetryLogic, service::AzureBlobService, sink::AzureBlobSink,
+            self,
+            config::{AzureBlobRetryLogic, BlobAccessTier, BlobEncryption},
+            service::AzureBlobService,
+            sink::AzureBlobSink,
         },
         util::{
             partitioner::KeyPartitioner, BatchConfig, BulkSizeBasedDefaultBatchSettings,
@@ -89,6 +95,60 @@ pub struct AzureBlobSinkConfig {
     /// blob keys must be unique.
     pub blob_append_uuid: Option<bool>,
 
+    /// Whether or not to send an MD5 checksum of the blob contents with each upload.
+    ///
+    /// When enabled, Azure Blob Storage verifies the uploaded content against the checksum and
+    /// rejects the upload if they do not match. Rejected uploads are retried.
+    #[serde(default)]
+    pub verify_checksum: bool,
+
+    /// The name of the encryption scope to encrypt uploaded blobs with.
+    ///
+    /// The encryption scope must already exist in the storage account. Cannot be used together
+    /// with `customer_provided_key`.
+    pub encryption_scope: Option<String>,
+
+    /// A base64-encoded AES-256 key to encrypt uploaded blobs with.
+    ///
+    /// The key is sent with each upload and is not stored by Azure Blob Storage, so the same key
+    /// is needed to read the blobs back. Cannot be used together with `encryption_scope`.
+    pub customer_provided_key: Option<SensitiveString>,
+
+    /// The access tier to upload blobs to.
+    ///
+    /// If unset, blobs are uploaded to the default access tier of the storage account.
+    #[configurable(derived)]
+    pub access_tier: Option<BlobAccessTier>,
+
+    /// A local directory to write uploads that permanently failed to.
+    ///
+    /// When set, the body of an upload that fails with an error that is not retried, such as a
+    /// `403 Forbidden` response, is written to a new file in this directory named after its blob
+    /// followed by a UUID. Otherwise, the body of such an upload is dropped. Either way, its events
+    /// are reported as rejected rather than delivered.
+    #[configurable(metadata(docs::examples = "/var/lib/vector/azure_blob_dlq"))]
+    pub dlq_path: Option<PathBuf>,
+
+    /// Whether the healthcheck also checks that blobs can be written to the container.
+    ///
+    /// When enabled, the healthcheck uploads an empty blob to the container and deletes it again,
+    /// so that missing write permissions are reported at startup instead of on the first upload.
+    /// This also requires permission to delete blobs. It is disabled by default, as it writes to
+    /// the container. Cannot be used together with the `archive` access tier.
+    #[serde(default)]
+    pub healthcheck_write_probe: bool,
+
+    /// Formatting options for blobs encoded with the `json` codec.
+    ///
+    /// Cannot be used with any other codec, and `pretty` cannot be used with newline-delimited
+    /// framing.
+    #[configurable(derived)]
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub json: JsonSerializerOptions,
+
     #[serde(flatten)]
     pub encoding: EncodingConfigWithFraming,
 
@@ -122,6 +182,13 @@ impl GenerateConfig for AzureBlobSinkConfig {
             blob_prefix: Some(String::from("blob")),
             blob_time_format: Some(String::from("%s")),
             blob_append_uuid: Some(true),
+            verify_checksum: false,
+            encryption_scope: None,
+            customer_provided_key: None,
+            access_tier: None,
+            dlq_path: None,
+            healthcheck_write_probe: false,
+            json: JsonSerializerOptions::default(),
             encoding: (Some(NewlineDelimitedEncoderConfig::new()), JsonSerializerConfig::default()).into(),
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
@@ -135,6 +202,12 @@ impl GenerateConfig for AzureBlobSinkConfig {
 #[async_trait::async_trait]
 impl SinkConfig for AzureBlobSinkConfig {
     async fn build(&self, _cx: SinkContext) -> Result<(VectorSink, Healthcheck)> {
+        if self.healthcheck_write_probe && self.access_tier == Some(BlobAccessTier::Archive) {
+            return Err(
+                "`healthcheck_write_probe` can't be enabled with the `archive` access tier".into(),
+            );
+        }
+
         let client = azure_common::config::build_client(
             self.connection_string
                 .as_ref()
@@ -146,6 +219,7 @@ impl SinkConfig for AzureBlobSinkConfig {
         let healthcheck = azure_common::config::build_healthcheck(
             self.container_name.clone(),
             Arc::clone(&client),
+            self.healthcheck_write_probe,
         )?;
         let sink = self.build_processor(client)?;
         Ok((sink, healthcheck))
@@ -172,7 +246,7 @@ impl AzureBlobSinkConfig {
         let request_limits = self.request.unwrap_with(&DEFAULT_REQUEST_LIMITS);
         let service = ServiceBuilder::new()
             .settings(request_limits, AzureBlobRetryLogic)
-            .service(AzureBlobService::new(client));
+            .service(AzureBlobService::new(client, self.dlq_path.clone()));
 
         // Configure our partitioning/batching.
         let batcher_settings = self.batch.into_batcher_settings()?;
@@ -187,13 +261,15 @@ impl AzureBlobSinkConfig {
             .unwrap_or(DEFAULT_FILENAME_APPEND_UUID);
 
         let transformer = self.encoding.transformer();
-        let (framer, serializer) = self.encoding.build(SinkType::MessageBased)?;
-        let encoder = Encoder::<Framer>::new(framer, serializer);
+        let encoder = self.encoder()?;
 
         let request_options = AzureBlobRequestOptions {
             container_name: self.container_name.clone(),
             blob_time_format,
             blob_append_uuid,
+            verify_checksum: self.verify_checksum,
+            encryption: self.encryption()?,
+            access_tier: self.access_tier,
             encoder: (transformer, encoder),
             compression: self.compression,
         };
@@ -208,6 +284,37 @@ impl AzureBlobSinkConfig {
         Ok(VectorSink::from_event_streamsink(sink))
     }
 
+    pub(super) fn encoder(&self) -> crate::Result<Encoder<Framer>> {
+        let (framer, serializer) = self.encoding.build(SinkType::MessageBased)?;
+        let serializer = match self.encoding.config().1 {
+            SerializerConfig::Json(config) => {
+                if self.json.pretty && matches!(framer, Framer::NewlineDelimited(_)) {
+                    return Err(
+                        "`json.pretty` can't be enabled with newline-delimited framing".into(),
+                    );
+                }
+                JsonSerializer::with_options(config.metric_tag_values, self.json).into()
+            }
+            _ if self.json != JsonSerializerOptions::default() => {
+                return Err("`json` options can only be used with the `json` codec".into());
+            }
+            _ => serializer,
+        };
+        Ok(Encoder::<Framer>::new(framer, serializer))
+    }
+
+    pub(super) fn encryption(&self) -> crate::Result<Option<BlobEncryption>> {
+        match (&self.encryption_scope, &self.customer_provided_key) {
+            (Some(_), Some(_)) => Err(
+                "`encryption_scope` and `customer_provided_key` can't be provided at the same time"
+                    .into(),
+            ),
+            (Some(scope), None) => Ok(Some(BlobEncryption::Scope(scope.clone()))),
+            (None, Some(key)) => BlobEncryption::customer_key(key.inner()).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
     pub fn key_partitioner(&self) -> crate::Result<KeyPartitioner> {
         let blob_prefix = self
             .blob_prefix
@@ -17,15 +17,19 @@ use vector_core::ByteSizeOf;
 
 use super::config::AzureBlobSinkConfig;
 use crate::{
-    event::{Event, EventArray, LogEvent},
+    event::{BatchNotifier, BatchStatus, Event, EventArray, LogEvent},
     sinks::{
         azure_common,
-        util::{Compression, TowerRequestConfig},
+        util::{
+            test::{build_test_server, build_test_server_status},
+            Compression, TowerRequestConfig,
+        },
         VectorSink,
     },
     test_util::{
         components::{assert_sink_compliance, SINK_TAGS},
-        random_events_with_stream, random_lines, random_lines_with_stream, random_string,
+        next_addr, random_events_with_stream, random_lines, random_lines_with_stream,
+        random_string, temp_dir,
     },
 };
 
@@ -39,11 +43,33 @@ async fn azure_blob_healthcheck_passed() {
     )
     .expect("Failed to create client");
 
-    let response = azure_common::config::build_healthcheck(config.container_name, client);
+    let response = azure_common::config::build_healthcheck(config.container_name, client, false);
 
     response.expect("Failed to pass healthcheck");
 }
 
+#[tokio::test]
+async fn azure_blob_healthcheck_write_probe_passed() {
+    let config = AzureBlobSinkConfig::new_emulator().await;
+    let client = azure_common::config::build_client(
+        config.connection_string.clone().map(Into::into),
+        None,
+        config.container_name.clone(),
+    )
+    .expect("Failed to create client");
+
+    azure_common::config::build_healthcheck(config.container_name.clone(), client, true)
+        .unwrap()
+        .await
+        .expect("Failed to pass healthcheck");
+
+    // the probe blob is deleted again
+    assert!(config
+        .list_blobs(String::from("vector-healthcheck-"))
+        .await
+        .is_empty());
+}
+
 #[tokio::test]
 async fn azure_blob_healthcheck_unknown_container() {
     let config = AzureBlobSinkConfig::new_emulator().await;
@@ -59,7 +85,7 @@ async fn azure_blob_healthcheck_unknown_container() {
     .expect("Failed to create client");
 
     assert_eq!(
-        azure_common::config::build_healthcheck(config.container_name, client)
+        azure_common::config::build_healthcheck(config.container_name, client, false)
             .unwrap()
             .await
             .unwrap_err()
@@ -213,6 +239,57 @@ async fn azure_blob_rotate_files_after_the_buffer_size_is_reached() {
     }
 }
 
+#[tokio::test]
+async fn azure_blob_sends_encryption_scope() {
+    let addr = next_addr();
+    let (mut rx, _trigger, server) = build_test_server(addr);
+    tokio::spawn(server);
+
+    let config = AzureBlobSinkConfig {
+        connection_string: Some(format!("UseDevelopmentStorage=true;DefaultEndpointsProtocol=http;AccountName=devstoreaccount1;AccountKey=Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==;BlobEndpoint=http://{}/devstoreaccount1;", addr).into()),
+        encryption_scope: Some("vector-scope".to_string()),
+        ..AzureBlobSinkConfig::new_emulator().await
+    };
+    let (_, input) = random_lines_with_stream(100, 1, None);
+    tokio::spawn(config.to_sink().run(input));
+
+    let (parts, _) = rx.next().await.expect("no request received");
+    assert_eq!(
+        parts.headers.get("x-ms-encryption-scope").unwrap(),
+        "vector-scope"
+    );
+}
+
+#[tokio::test]
+async fn azure_blob_dead_letters_forbidden_upload() {
+    let addr = next_addr();
+    let (_rx, _trigger, server) = build_test_server_status(addr, StatusCode::FORBIDDEN);
+    tokio::spawn(server);
+
+    let dlq_path = temp_dir();
+    let config = AzureBlobSinkConfig {
+        connection_string: Some(format!("UseDevelopmentStorage=true;DefaultEndpointsProtocol=http;AccountName=devstoreaccount1;AccountKey=Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==;BlobEndpoint=http://{}/devstoreaccount1;", addr).into()),
+        dlq_path: Some(dlq_path.clone()),
+        ..AzureBlobSinkConfig::new_emulator().await
+    };
+    let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+    let (lines, input) = random_lines_with_stream(100, 10, Some(batch));
+    config
+        .to_sink()
+        .run(input)
+        .await
+        .expect("Running sink failed");
+    assert_eq!(receiver.try_recv(), Ok(BatchStatus::Rejected));
+
+    let files = std::fs::read_dir(&dlq_path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(files.len(), 1);
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    assert_eq!(lines, content.lines().collect::<Vec<_>>());
+}
+
 impl AzureBlobSinkConfig {
     pub async fn new_emulator() -> AzureBlobSinkConfig {
         let address = std::env::var("AZURE_ADDRESS").unwrap_or_else(|_| "localhost".into());
@@ -223,6 +300,13 @@ impl AzureBlobSinkConfig {
                 blob_prefix: None,
                 blob_time_format: None,
                 blob_append_uuid: None,
+                verify_checksum: false,
+                encryption_scope: None,
+                customer_provided_key: None,
+                access_tier: None,
+                dlq_path: None,
+                healthcheck_write_probe: false,
+                json: Default::default(),
                 encoding: (None::<FramingConfig>, TextSerializerConfig::default()).into(),
                 compression: Compression::None,
                 batch: Default::default(),
@@ -1,6 +1,7 @@
 use bytes::Bytes;
 use chrono::Utc;
 use codecs::encoding::Framer;
+use md5::Digest;
 use uuid::Uuid;
 use vector_common::request_metadata::RequestMetadata;
 use vector_core::ByteSizeOf;
@@ -9,7 +10,9 @@ use crate::{
     codecs::{Encoder, Transformer},
     event::{Event, Finalizable},
     sinks::{
-        azure_common::config::{AzureBlobMetadata, AzureBlobRequest},
+        azure_common::config::{
+            AzureBlobMetadata, AzureBlobRequest, BlobAccessTier, BlobEncryption,
+        },
         util::{
             metadata::RequestMetadataBuilder, request_builder::EncodeResult, Compression,
             RequestBuilder,
@@ -22,6 +25,9 @@ pub struct AzureBlobRequestOptions {
     pub container_name: String,
     pub blob_time_format: String,
     pub blob_append_uuid: bool,
+    pub verify_checksum: bool,
+    pub encryption: Option<BlobEncryption>,
+    pub access_tier: Option<BlobAccessTier>,
     pub encoder: (Transformer, Encoder<Framer>),
     pub compression: Compression,
 }
@@ -81,6 +87,9 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
         );
 
         let blob_data = payload.into_payload();
+        let content_md5 = self
+            .verify_checksum
+            .then(|| md5::Md5::digest(&blob_data).into());
 
         debug!(
             message = "Sending events.",
@@ -94,6 +103,9 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
             blob_data,
             content_encoding: self.compression.content_encoding(),
             content_type: self.compression.content_type(),
+            content_md5,
+            encryption: self.encryption.clone(),
+            access_tier: self.access_tier,
             metadata: azure_metadata,
             request_metadata,
         }
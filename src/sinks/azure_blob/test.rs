@@ -2,15 +2,21 @@ use bytes::Bytes;
 use chrono::Utc;
 use codecs::{
     encoding::{Framer, FramingConfig},
-    NewlineDelimitedEncoder, TextSerializerConfig,
+    JsonSerializerConfig, JsonSerializerOptions, NewlineDelimitedEncoder,
+    NewlineDelimitedEncoderConfig, TextSerializerConfig,
 };
+use futures::StreamExt;
+use vector_common::btreemap;
 use vector_core::partition::Partitioner;
 
 use super::config::AzureBlobSinkConfig;
 use super::request_builder::AzureBlobRequestOptions;
 use crate::codecs::EncodingConfigWithFraming;
+use crate::config::{SinkConfig, SinkContext};
 use crate::event::{Event, LogEvent};
-use crate::sinks::util::{request_builder::RequestBuilder, Compression};
+use crate::sinks::azure_common::config::BlobAccessTier;
+use crate::sinks::util::{request_builder::RequestBuilder, test::build_test_server, Compression};
+use crate::test_util::{next_addr, random_lines_with_stream};
 use crate::{codecs::Encoder, sinks::util::request_builder::EncodeResult};
 
 fn default_config(encoding: EncodingConfigWithFraming) -> AzureBlobSinkConfig {
@@ -21,6 +27,13 @@ fn default_config(encoding: EncodingConfigWithFraming) -> AzureBlobSinkConfig {
         blob_prefix: Default::default(),
         blob_time_format: Default::default(),
         blob_append_uuid: Default::default(),
+        verify_checksum: Default::default(),
+        encryption_scope: Default::default(),
+        customer_provided_key: Default::default(),
+        access_tier: Default::default(),
+        dlq_path: Default::default(),
+        healthcheck_write_probe: Default::default(),
+        json: Default::default(),
         encoding,
         compression: Compression::gzip_default(),
         batch: Default::default(),
@@ -57,6 +70,9 @@ fn azure_blob_build_request_without_compression() {
         container_name,
         blob_time_format,
         blob_append_uuid,
+        verify_checksum: false,
+        encryption: None,
+        access_tier: None,
         encoder: (
             Default::default(),
             Encoder::<Framer>::new(
@@ -77,6 +93,102 @@ fn azure_blob_build_request_without_compression() {
     assert_eq!(request.metadata.partition_key, "blob.log".to_string());
     assert_eq!(request.content_encoding, None);
     assert_eq!(request.content_type, "text/plain");
+    assert_eq!(request.access_tier, None);
+}
+
+#[test]
+fn azure_blob_build_request_with_access_tier() {
+    let log = Event::Log(LogEvent::from("test message"));
+    let sink_config: AzureBlobSinkConfig = toml::from_str(
+        r#"
+            connection_string = "UseDevelopmentStorage=true"
+            container_name = "logs"
+            access_tier = "cool"
+            encoding.codec = "text"
+        "#,
+    )
+    .unwrap();
+
+    let key = sink_config
+        .key_partitioner()
+        .unwrap()
+        .partition(&log)
+        .expect("key wasn't provided");
+
+    let request_options = AzureBlobRequestOptions {
+        container_name: sink_config.container_name.clone(),
+        blob_time_format: String::from(""),
+        blob_append_uuid: false,
+        verify_checksum: false,
+        encryption: None,
+        access_tier: sink_config.access_tier,
+        encoder: (
+            Default::default(),
+            Encoder::<Framer>::new(
+                NewlineDelimitedEncoder::new().into(),
+                TextSerializerConfig::default().build().into(),
+            ),
+        ),
+        compression: Compression::None,
+    };
+
+    let (metadata, request_metadata_builder, _events) =
+        request_options.split_input((key, vec![log]));
+
+    let payload = EncodeResult::uncompressed(Bytes::new());
+    let request_metadata = request_metadata_builder.build(&payload);
+    let request = request_options.build_request(metadata, request_metadata, payload);
+
+    assert_eq!(request.access_tier, Some(BlobAccessTier::Cool));
+}
+
+#[tokio::test]
+async fn azure_blob_sends_access_tier_header() {
+    let addr = next_addr();
+    let (mut rx, _trigger, server) = build_test_server(addr);
+    tokio::spawn(server);
+
+    let sink_config: AzureBlobSinkConfig = toml::from_str(&format!(
+        r#"
+            connection_string = "UseDevelopmentStorage=true;DefaultEndpointsProtocol=http;AccountName=devstoreaccount1;AccountKey=Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==;BlobEndpoint=http://{}/devstoreaccount1;"
+            container_name = "logs"
+            access_tier = "cool"
+            encoding.codec = "text"
+        "#,
+        addr
+    ))
+    .unwrap();
+    let (sink, _) = sink_config.build(SinkContext::new_test()).await.unwrap();
+    let (_, input) = random_lines_with_stream(100, 1, None);
+    tokio::spawn(sink.run(input));
+
+    let (parts, _) = rx.next().await.expect("no request received");
+    assert_eq!(parts.headers.get("x-ms-access-tier").unwrap(), "Cool");
+}
+
+#[tokio::test]
+async fn azure_blob_archive_access_tier_with_write_probe() {
+    let sink_config: AzureBlobSinkConfig = toml::from_str(
+        r#"
+            connection_string = "UseDevelopmentStorage=true"
+            container_name = "logs"
+            access_tier = "archive"
+            healthcheck_write_probe = true
+            encoding.codec = "text"
+        "#,
+    )
+    .unwrap();
+
+    let error = sink_config
+        .build(SinkContext::new_test())
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        error,
+        "`healthcheck_write_probe` can't be enabled with the `archive` access tier"
+    );
 }
 
 #[test]
@@ -102,6 +214,9 @@ fn azure_blob_build_request_with_compression() {
         container_name,
         blob_time_format,
         blob_append_uuid,
+        verify_checksum: false,
+        encryption: None,
+        access_tier: None,
         encoder: (
             Default::default(),
             Encoder::<Framer>::new(
@@ -146,6 +261,9 @@ fn azure_blob_build_request_with_time_format() {
         container_name,
         blob_time_format,
         blob_append_uuid,
+        verify_checksum: false,
+        encryption: None,
+        access_tier: None,
         encoder: (
             Default::default(),
             Encoder::<Framer>::new(
@@ -194,6 +312,9 @@ fn azure_blob_build_request_with_uuid() {
         container_name,
         blob_time_format,
         blob_append_uuid,
+        verify_checksum: false,
+        encryption: None,
+        access_tier: None,
         encoder: (
             Default::default(),
             Encoder::<Framer>::new(
@@ -215,3 +336,234 @@ fn azure_blob_build_request_with_uuid() {
     assert_eq!(request.content_encoding, None);
     assert_eq!(request.content_type, "text/plain");
 }
+
+#[test]
+fn azure_blob_build_request_with_checksum() {
+    let log = Event::Log(LogEvent::from("test message"));
+    let sink_config = AzureBlobSinkConfig {
+        blob_prefix: Some("blob".into()),
+        ..default_config((None::<FramingConfig>, TextSerializerConfig::default()).into())
+    };
+
+    let key = sink_config
+        .key_partitioner()
+        .unwrap()
+        .partition(&log)
+        .expect("key wasn't provided");
+
+    let request_options = AzureBlobRequestOptions {
+        container_name: String::from("logs"),
+        blob_time_format: String::from(""),
+        blob_append_uuid: false,
+        verify_checksum: true,
+        encryption: None,
+        access_tier: None,
+        encoder: (
+            Default::default(),
+            Encoder::<Framer>::new(
+                NewlineDelimitedEncoder::new().into(),
+                TextSerializerConfig::default().build().into(),
+            ),
+        ),
+        compression: Compression::None,
+    };
+
+    let (metadata, request_metadata_builder, _events) =
+        request_options.split_input((key, vec![log]));
+
+    let payload = EncodeResult::uncompressed(Bytes::from_static(b"hello"));
+    let request_metadata = request_metadata_builder.build(&payload);
+    let request = request_options.build_request(metadata, request_metadata, payload);
+
+    // MD5 of "hello".
+    assert_eq!(
+        request.content_md5,
+        Some([
+            0x5d, 0x41, 0x40, 0x2a, 0xbc, 0x4b, 0x2a, 0x76, 0xb9, 0x71, 0x9d, 0x91, 0x10, 0x17,
+            0xc5, 0x92
+        ])
+    );
+}
+
+#[test]
+fn azure_blob_build_request_with_json_formatting() {
+    let encode = |framing: Option<FramingConfig>, json: JsonSerializerOptions| {
+        let sink_config = AzureBlobSinkConfig {
+            json,
+            ..default_config((framing, JsonSerializerConfig::default()).into())
+        };
+        let request_options = AzureBlobRequestOptions {
+            container_name: String::from("logs"),
+            blob_time_format: String::from(""),
+            blob_append_uuid: false,
+            verify_checksum: false,
+            encryption: None,
+            access_tier: None,
+            encoder: (
+                sink_config.encoding.transformer(),
+                sink_config.encoder().unwrap(),
+            ),
+            compression: Compression::None,
+        };
+
+        let events = vec![
+            Event::Log(LogEvent::from(btreemap! {
+                "message" => "first",
+                "city" => "Zürich",
+            })),
+            Event::Log(LogEvent::from(btreemap! {
+                "message" => "second",
+                "city" => "Malmö",
+            })),
+        ];
+        let payload = request_options.encode_events(events).unwrap();
+        String::from_utf8(payload.into_payload().to_vec()).unwrap()
+    };
+    let newline_delimited = || Some(NewlineDelimitedEncoderConfig::new().into());
+
+    let compact = encode(newline_delimited(), JsonSerializerOptions::default());
+    assert_eq!(
+        compact,
+        "{\"city\":\"Zürich\",\"message\":\"first\"}\n{\"city\":\"Malmö\",\"message\":\"second\"}"
+    );
+
+    let pretty = encode(
+        None,
+        JsonSerializerOptions {
+            pretty: true,
+            escape_non_ascii: false,
+        },
+    );
+    assert_eq!(
+        pretty,
+        "[{\n  \"city\": \"Zürich\",\n  \"message\": \"first\"\n},{\n  \"city\": \"Malmö\",\n  \"message\": \"second\"\n}]"
+    );
+
+    let escaped = encode(
+        newline_delimited(),
+        JsonSerializerOptions {
+            pretty: false,
+            escape_non_ascii: true,
+        },
+    );
+    assert!(escaped.is_ascii());
+    assert_eq!(
+        escaped,
+        r#"{"city":"Z\u00fcrich","message":"first"}
+{"city":"Malm\u00f6","message":"second"}"#
+    );
+}
+
+#[test]
+fn azure_blob_json_pretty_with_newline_delimited_framing() {
+    let sink_config = AzureBlobSinkConfig {
+        json: JsonSerializerOptions {
+            pretty: true,
+            escape_non_ascii: false,
+        },
+        ..default_config(
+            (
+                Some(NewlineDelimitedEncoderConfig::new()),
+                JsonSerializerConfig::default(),
+            )
+                .into(),
+        )
+    };
+
+    let error = sink_config.encoder().err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "`json.pretty` can't be enabled with newline-delimited framing"
+    );
+}
+
+#[test]
+fn azure_blob_json_options_with_other_codec() {
+    let sink_config = AzureBlobSinkConfig {
+        json: JsonSerializerOptions {
+            pretty: false,
+            escape_non_ascii: true,
+        },
+        ..default_config((None::<FramingConfig>, TextSerializerConfig::default()).into())
+    };
+
+    let error = sink_config.encoder().err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "`json` options can only be used with the `json` codec"
+    );
+}
+
+#[test]
+fn azure_blob_build_request_with_compression_level() {
+    let config_with_level = |level: u32| {
+        toml::from_str::<AzureBlobSinkConfig>(&format!(
+            r#"
+            connection_string = "UseDevelopmentStorage=true"
+            container_name = "logs"
+            encoding.codec = "text"
+            compression = {{ algorithm = "gzip", level = {} }}
+            "#,
+            level
+        ))
+    };
+    assert!(config_with_level(10).is_err());
+
+    let encode = |compression: Compression| {
+        let request_options = AzureBlobRequestOptions {
+            container_name: String::from("logs"),
+            blob_time_format: String::from(""),
+            blob_append_uuid: false,
+            verify_checksum: false,
+            encryption: None,
+            access_tier: None,
+            encoder: (
+                Default::default(),
+                Encoder::<Framer>::new(
+                    NewlineDelimitedEncoder::new().into(),
+                    TextSerializerConfig::default().build().into(),
+                ),
+            ),
+            compression,
+        };
+
+        let events = (0..1000)
+            .map(|i| Event::Log(LogEvent::from(format!("test message {}", i % 10))))
+            .collect();
+        let payload = request_options.encode_events(events).unwrap();
+        payload.into_payload().len()
+    };
+
+    let fast = encode(config_with_level(1).unwrap().compression);
+    let best = encode(config_with_level(9).unwrap().compression);
+    assert!(best <= fast);
+}
+
+#[test]
+fn azure_blob_encryption_headers() {
+    // A 256-bit key of all zeroes.
+    let key = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    let sink_config = AzureBlobSinkConfig {
+        customer_provided_key: Some(key.to_string().into()),
+        ..default_config((None::<FramingConfig>, TextSerializerConfig::default()).into())
+    };
+
+    let encryption = sink_config.encryption().unwrap().unwrap();
+    assert_eq!(
+        encryption.headers(),
+        vec![
+            ("x-ms-encryption-key", key.to_string()),
+            (
+                "x-ms-encryption-key-sha256",
+                "Zmh6rfhivXdsj8GLjp+OIAiXFIVu4jOzkCpZHQ1fKSU=".to_string()
+            ),
+            ("x-ms-encryption-algorithm", "AES256".to_string()),
+        ]
+    );
+
+    let sink_config = AzureBlobSinkConfig {
+        encryption_scope: Some("scope".into()),
+        ..sink_config
+    };
+    assert!(sink_config.encryption().is_err());
+}
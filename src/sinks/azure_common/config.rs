@@ -7,7 +7,9 @@ use azure_storage_blobs::{blob::operations::PutBlockBlobResponse, prelude::*};
 use bytes::Bytes;
 use futures::FutureExt;
 use http::StatusCode;
+use sha2::{Digest, Sha256};
 use snafu::Snafu;
+use uuid::Uuid;
 use vector_common::request_metadata::{MetaDescriptive, RequestMetadata};
 use vector_core::{internal_event::CountByteSize, stream::DriverResponse};
 
@@ -15,12 +17,17 @@ use crate::{
     event::{EventFinalizers, EventStatus, Finalizable},
     sinks::{util::retries::RetryLogic, Healthcheck},
 };
+use vector_common::sensitive_string::SensitiveString;
+use vector_config::configurable_component;
 
 #[derive(Debug, Clone)]
 pub struct AzureBlobRequest {
     pub blob_data: Bytes,
     pub content_encoding: Option<&'static str>,
     pub content_type: &'static str,
+    pub content_md5: Option<[u8; 16]>,
+    pub encryption: Option<BlobEncryption>,
+    pub access_tier: Option<BlobAccessTier>,
     pub metadata: AzureBlobMetadata,
     pub request_metadata: RequestMetadata,
 }
@@ -37,6 +44,73 @@ impl MetaDescriptive for AzureBlobRequest {
     }
 }
 
+/// The access tier of uploaded blobs.
+#[configurable_component]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobAccessTier {
+    /// Optimized for data that is accessed frequently.
+    Hot,
+
+    /// Optimized for data that is accessed infrequently and stored for at least 30 days.
+    Cool,
+
+    /// Optimized for data that is rarely accessed and stored for at least 180 days.
+    ///
+    /// Blobs in this tier are offline, and must be rehydrated to another tier before they can be
+    /// read.
+    Archive,
+}
+
+impl From<BlobAccessTier> for AccessTier {
+    fn from(tier: BlobAccessTier) -> Self {
+        match tier {
+            BlobAccessTier::Hot => AccessTier::Hot,
+            BlobAccessTier::Cool => AccessTier::Cool,
+            BlobAccessTier::Archive => AccessTier::Archive,
+        }
+    }
+}
+
+/// Server-side encryption requested for uploaded blobs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlobEncryption {
+    /// Encrypt with the key of an encryption scope of the storage account.
+    Scope(String),
+    /// Encrypt with a key sent along with each upload.
+    CustomerKey {
+        key: SensitiveString,
+        key_sha256: String,
+    },
+}
+
+impl BlobEncryption {
+    /// Builds the encryption for a base64-encoded AES-256 key.
+    pub fn customer_key(key: &str) -> crate::Result<Self> {
+        let decoded =
+            base64::decode(key).map_err(|_| "`customer_provided_key` must be base64-encoded")?;
+        if decoded.len() != 32 {
+            return Err("`customer_provided_key` must be a 256-bit key".into());
+        }
+        Ok(Self::CustomerKey {
+            key: key.to_string().into(),
+            key_sha256: base64::encode(Sha256::digest(&decoded)),
+        })
+    }
+
+    /// The `x-ms-encryption-*` headers to send with each upload.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Scope(scope) => vec![("x-ms-encryption-scope", scope.clone())],
+            Self::CustomerKey { key, key_sha256 } => vec![
+                ("x-ms-encryption-key", key.inner().to_string()),
+                ("x-ms-encryption-key-sha256", key_sha256.clone()),
+                ("x-ms-encryption-algorithm", "AES256".to_string()),
+            ],
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AzureBlobMetadata {
     pub partition_key: String,
@@ -55,12 +129,17 @@ impl RetryLogic for AzureBlobRetryLogic {
     fn is_retriable_error(&self, error: &Self::Error) -> bool {
         error.status().is_server_error()
             || StatusCode::TOO_MANY_REQUESTS.as_u16() == Into::<u16>::into(error.status())
+            // The uploaded content didn't match the checksum we sent, so it was likely corrupted
+            // in transit.
+            || error.error_code() == Some("Md5Mismatch")
     }
 }
 
 #[derive(Debug)]
 pub struct AzureBlobResponse {
-    pub inner: PutBlockBlobResponse,
+    /// The response to the upload, or `None` if it permanently failed and the request was written
+    /// to the dead-letter path instead.
+    pub inner: Option<PutBlockBlobResponse>,
     pub count: usize,
     pub events_byte_size: usize,
     pub byte_size: usize,
@@ -68,7 +147,12 @@ pub struct AzureBlobResponse {
 
 impl DriverResponse for AzureBlobResponse {
     fn event_status(&self) -> EventStatus {
-        EventStatus::Delivered
+        // Dead-lettered uploads were not delivered to Azure Blob Storage.
+        if self.inner.is_some() {
+            EventStatus::Delivered
+        } else {
+            EventStatus::Rejected
+        }
     }
 
     fn events_sent(&self) -> CountByteSize {
@@ -86,35 +170,73 @@ pub enum HealthcheckError {
     InvalidCredentials,
     #[snafu(display("Container: {:?} not found", container))]
     UnknownContainer { container: String },
+    #[snafu(display("Not allowed to write to container: {:?}", container))]
+    WriteForbidden { container: String },
     #[snafu(display("Unknown status code: {}", status))]
     Unknown { status: StatusCode },
 }
 
+/// Builds a healthcheck that fetches the properties of the container. With `write_probe`, it also
+/// uploads an empty blob to the container and deletes it again.
 pub fn build_healthcheck(
     container_name: String,
     client: Arc<ContainerClient>,
+    write_probe: bool,
 ) -> crate::Result<Healthcheck> {
-    let healthcheck = async move {
-        let response = client.get_properties().into_future().await;
-
-        let resp: crate::Result<()> = match response {
-            Ok(_) => Ok(()),
-            Err(reason) => Err(match reason.downcast_ref::<HttpError>() {
-                Some(err) => match StatusCode::from_u16(err.status().into()) {
-                    Ok(StatusCode::FORBIDDEN) => Box::new(HealthcheckError::InvalidCredentials),
-                    Ok(StatusCode::NOT_FOUND) => Box::new(HealthcheckError::UnknownContainer {
-                        container: container_name,
-                    }),
-                    Ok(status) => Box::new(HealthcheckError::Unknown { status }),
-                    Err(_) => "unknown status code".into(),
-                },
-                _ => reason.into(),
-            }),
+    Ok(healthcheck(container_name, client, write_probe).boxed())
+}
+
+async fn healthcheck(
+    container_name: String,
+    client: Arc<ContainerClient>,
+    write_probe: bool,
+) -> crate::Result<()> {
+    client
+        .get_properties()
+        .into_future()
+        .await
+        .map_err(|reason| {
+            healthcheck_error(
+                reason,
+                HealthcheckError::InvalidCredentials,
+                &container_name,
+            )
+        })?;
+
+    if write_probe {
+        let write_error = |reason| {
+            let forbidden = HealthcheckError::WriteForbidden {
+                container: container_name.clone(),
+            };
+            healthcheck_error(reason, forbidden, &container_name)
         };
-        resp
-    };
+        let blob = client.blob_client(format!("vector-healthcheck-{}", Uuid::new_v4()));
+        blob.put_block_blob(Bytes::new())
+            .into_future()
+            .await
+            .map_err(write_error)?;
+        blob.delete().into_future().await.map_err(write_error)?;
+    }
 
-    Ok(healthcheck.boxed())
+    Ok(())
+}
+
+fn healthcheck_error(
+    reason: azure_core::error::Error,
+    forbidden: HealthcheckError,
+    container_name: &str,
+) -> crate::Error {
+    match reason.downcast_ref::<HttpError>() {
+        Some(err) => match StatusCode::from_u16(err.status().into()) {
+            Ok(StatusCode::FORBIDDEN) => Box::new(forbidden),
+            Ok(StatusCode::NOT_FOUND) => Box::new(HealthcheckError::UnknownContainer {
+                container: container_name.to_owned(),
+            }),
+            Ok(status) => Box::new(HealthcheckError::Unknown { status }),
+            Err(_) => "unknown status code".into(),
+        },
+        _ => reason.into(),
+    }
 }
 
 pub fn build_client(
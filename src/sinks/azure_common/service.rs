@@ -1,27 +1,73 @@
 use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
     result::Result as StdResult,
     sync::Arc,
     task::{Context, Poll},
 };
 
+use azure_core::{
+    error::HttpError,
+    headers::{HeaderName, HeaderValue},
+    CustomHeaders,
+};
 use azure_storage_blobs::prelude::*;
+use bytes::Bytes;
 use futures::future::BoxFuture;
+use tokio::io::AsyncWriteExt;
 use tower::Service;
 use tracing::Instrument;
+use uuid::Uuid;
 
-use crate::sinks::azure_common::config::{AzureBlobRequest, AzureBlobResponse};
+use crate::{
+    internal_events::{AzureBlobDeadLetterWriteError, AzureBlobDeadLettered},
+    sinks::{
+        azure_common::config::{AzureBlobRequest, AzureBlobResponse, AzureBlobRetryLogic},
+        util::retries::RetryLogic,
+    },
+};
 
 #[derive(Clone)]
 pub(crate) struct AzureBlobService {
     client: Arc<ContainerClient>,
+    dlq_path: Option<PathBuf>,
 }
 
 impl AzureBlobService {
-    pub fn new(client: Arc<ContainerClient>) -> AzureBlobService {
-        AzureBlobService { client }
+    pub fn new(client: Arc<ContainerClient>, dlq_path: Option<PathBuf>) -> AzureBlobService {
+        AzureBlobService { client, dlq_path }
     }
 }
 
+/// Whether an upload failed in a way that retrying it won't fix.
+fn is_permanent_failure(error: &azure_core::Error) -> bool {
+    error.downcast_ref::<HttpError>().map_or(false, |error| {
+        !AzureBlobRetryLogic.is_retriable_error(error)
+    })
+}
+
+/// Writes the body of a failed upload to a new file in `dlq_path` named after its blob.
+///
+/// A UUID is appended to the name, so dead letters for the same blob, or for blobs whose names
+/// only differ by `/` and `_`, never overwrite each other.
+async fn write_dead_letter(
+    dlq_path: &Path,
+    blob_name: &str,
+    body: &Bytes,
+) -> std::io::Result<PathBuf> {
+    tokio::fs::create_dir_all(dlq_path).await?;
+    let file_name = format!("{}.{}", blob_name.replace('/', "_"), Uuid::new_v4());
+    let path = dlq_path.join(file_name);
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await?;
+    file.write_all(body).await?;
+    file.flush().await?;
+    Ok(path)
+}
+
 impl Service<AzureBlobRequest> for AzureBlobService {
     type Response = AzureBlobResponse;
     type Error = Box<dyn std::error::Error + std::marker::Send + std::marker::Sync>;
@@ -41,6 +87,7 @@ impl Service<AzureBlobRequest> for AzureBlobService {
                 .client
                 .blob_client(request.metadata.partition_key.as_str());
             let byte_size = request.blob_data.len();
+            let body = request.blob_data.clone();
             let blob = client
                 .put_block_blob(request.blob_data)
                 .content_type(request.content_type);
@@ -48,14 +95,58 @@ impl Service<AzureBlobRequest> for AzureBlobService {
                 Some(encoding) => blob.content_encoding(encoding),
                 None => blob,
             };
+            let blob = match request.content_md5 {
+                Some(md5) => blob.hash(Hash::MD5(md5)),
+                None => blob,
+            };
+            let blob = match request.access_tier {
+                Some(tier) => blob.access_tier(AccessTier::from(tier)),
+                None => blob,
+            };
+            let blob = match &request.encryption {
+                Some(encryption) => {
+                    let headers = encryption
+                        .headers()
+                        .into_iter()
+                        .map(|(name, value)| {
+                            (HeaderName::from_static(name), HeaderValue::from(value))
+                        })
+                        .collect::<HashMap<_, _>>();
+                    let mut context = azure_core::Context::new();
+                    context.insert(CustomHeaders::from(headers));
+                    blob.context(context)
+                }
+                None => blob,
+            };
 
             let result = blob
                 .into_future()
                 .instrument(info_span!("request").or_current())
-                .await
-                .map_err(|err| err.into());
+                .await;
 
-            result.map(|inner| AzureBlobResponse {
+            let inner = match (result, &this.dlq_path) {
+                (Ok(inner), _) => Some(inner),
+                (Err(error), Some(dlq_path)) if is_permanent_failure(&error) => {
+                    let blob_name = &request.metadata.partition_key;
+                    match write_dead_letter(dlq_path, blob_name, &body).await {
+                        Ok(path) => {
+                            emit!(AzureBlobDeadLettered {
+                                error: &error,
+                                path: &path,
+                                count: request.metadata.count,
+                            });
+                            None
+                        }
+                        Err(write_error) => {
+                            emit!(AzureBlobDeadLetterWriteError { error: write_error });
+                            return Err(error.into());
+                        }
+                    }
+                }
+                (Err(error), _) => return Err(error.into()),
+            };
+
+            Ok(AzureBlobResponse {
                 inner,
                 count: request.metadata.count,
                 events_byte_size: request.metadata.byte_size,
@@ -64,3 +155,30 @@ impl Service<AzureBlobRequest> for AzureBlobService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[tokio::test]
+    async fn dead_letters_do_not_overwrite_each_other() {
+        let dlq_path = temp_dir();
+        let mut paths = Vec::new();
+        for (blob_name, body) in [
+            ("a/b.log", "first"),
+            ("a/b.log", "second"),
+            ("a_b.log", "third"),
+        ] {
+            let path = write_dead_letter(&dlq_path, blob_name, &Bytes::from(body))
+                .await
+                .unwrap();
+            paths.push((path, body));
+        }
+
+        assert_eq!(std::fs::read_dir(&dlq_path).unwrap().count(), 3);
+        for (path, body) in paths {
+            assert_eq!(std::fs::read_to_string(path).unwrap(), body);
+        }
+    }
+}
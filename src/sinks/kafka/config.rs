@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use codecs::JsonSerializerConfig;
 use futures::FutureExt;
@@ -12,7 +12,10 @@ use crate::{
     serde::json::to_string,
     sinks::{
         kafka::sink::{healthcheck, KafkaSink},
-        util::{BatchConfig, NoDefaultsBatchSettings},
+        util::{
+            retries::{FixedRetryPolicy, RetryLogic},
+            BatchConfig, NoDefaultsBatchSettings,
+        },
         Healthcheck, VectorSink,
     },
 };
@@ -37,7 +40,12 @@ pub struct KafkaSinkConfig {
     ///
     /// If the field does not exist in the log or in tags, a blank value will be used. If unspecified, the key is not sent.
     ///
+    /// A template such as `{{ user_id }}` can also be given to build the key from the event. If
+    /// the template fails to render, for example because a field it references is missing, the
+    /// key is not sent.
+    ///
     /// Kafka uses a hash of the key to choose the partition or uses round-robin if the record has no key.
+    #[configurable(metadata(docs::templateable))]
     pub key_field: Option<String>,
 
     #[configurable(derived)]
@@ -64,6 +72,24 @@ pub struct KafkaSinkConfig {
     #[serde(default = "default_message_timeout_ms")]
     pub message_timeout_ms: u64,
 
+    /// The time, in milliseconds, after which a send that has not been acknowledged is considered slow.
+    ///
+    /// Sends that take longer than this are counted in the `kafka_slow_sends_total` metric. If
+    /// unspecified, sends are never considered slow.
+    pub slow_send_threshold_ms: Option<u64>,
+
+    /// The time, in milliseconds, to wait for a send to be acknowledged before failing it.
+    ///
+    /// A send that times out fails with a `MessageTimedOut` error, freeing its slot for other
+    /// sends. If unspecified, sends wait for an acknowledgement indefinitely.
+    ///
+    /// Timed out sends are retried according to the `request` settings.
+    pub send_timeout_ms: Option<u64>,
+
+    #[configurable(derived)]
+    #[serde(default)]
+    pub request: KafkaRetryConfig,
+
     /// A map of advanced options to pass directly to the underlying `librdkafka` client.
     ///
     /// For more information on configuration options, see [Configuration properties][config_props_docs].
@@ -74,10 +100,27 @@ pub struct KafkaSinkConfig {
 
     /// The log field name to use for the Kafka headers.
     ///
+    /// The field must hold an object, whose keys and values become the headers. Values that are not
+    /// strings are converted to strings, and null values are skipped.
+    ///
     /// If omitted, no headers will be written.
     #[serde(alias = "headers_field")] // accidentally released as `headers_field` in 0.18
     pub headers_key: Option<String>,
 
+    /// The maximum number of headers to write per message.
+    ///
+    /// Headers beyond this count are dropped, and the number dropped is reported in the
+    /// `kafka_headers_dropped_total` metric. This should not exceed the limits of the brokers,
+    /// which otherwise reject the message. If unspecified, there is no limit.
+    pub max_headers: Option<usize>,
+
+    /// The maximum total size of the headers to write per message, in bytes.
+    ///
+    /// The size of a header is the length of its key plus the length of its value. Headers that
+    /// would exceed this size are dropped, and the number dropped is reported in the
+    /// `kafka_headers_dropped_total` metric. If unspecified, there is no limit.
+    pub max_headers_bytes: Option<usize>,
+
     #[configurable(derived)]
     #[serde(
         default,
@@ -95,6 +138,64 @@ const fn default_message_timeout_ms() -> u64 {
     300000 // default in librdkafka
 }
 
+/// Settings for retrying failed sends.
+///
+/// Sends are delivered at least once: a send that timed out with a `MessageTimedOut` error is
+/// retried, even though the broker may have written it, which can produce duplicate messages.
+#[configurable_component]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaRetryConfig {
+    /// The maximum number of retries to make for a failed send.
+    ///
+    /// Set to `0` to disable retries.
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: usize,
+
+    /// The maximum amount of time, in seconds, to wait between retries.
+    #[serde(default = "default_retry_max_duration_secs")]
+    pub retry_max_duration_secs: u64,
+
+    /// The amount of time, in seconds, to wait before attempting the first retry for a failed send.
+    ///
+    /// After the first retry has failed, the fibonacci sequence will be used to select future backoffs.
+    #[serde(default = "default_retry_initial_backoff_secs")]
+    pub retry_initial_backoff_secs: u64,
+}
+
+const fn default_retry_attempts() -> usize {
+    5
+}
+
+const fn default_retry_max_duration_secs() -> u64 {
+    3_600
+}
+
+const fn default_retry_initial_backoff_secs() -> u64 {
+    1
+}
+
+impl Default for KafkaRetryConfig {
+    fn default() -> Self {
+        Self {
+            retry_attempts: default_retry_attempts(),
+            retry_max_duration_secs: default_retry_max_duration_secs(),
+            retry_initial_backoff_secs: default_retry_initial_backoff_secs(),
+        }
+    }
+}
+
+impl KafkaRetryConfig {
+    pub(crate) const fn retry_policy<L: RetryLogic>(&self, logic: L) -> FixedRetryPolicy<L> {
+        FixedRetryPolicy::new(
+            self.retry_attempts,
+            Duration::from_secs(self.retry_initial_backoff_secs),
+            Duration::from_secs(self.retry_max_duration_secs),
+            logic,
+        )
+    }
+}
+
 /// Used to determine the options to set in configs, since both Kafka consumers and producers have
 /// unique options, they use the same struct, and the error if given the wrong options.
 #[derive(Debug, PartialOrd, PartialEq, Eq)]
@@ -207,8 +308,13 @@ impl GenerateConfig for KafkaSinkConfig {
             auth: Default::default(),
             socket_timeout_ms: default_socket_timeout_ms(),
             message_timeout_ms: default_message_timeout_ms(),
+            slow_send_threshold_ms: None,
+            send_timeout_ms: None,
+            request: Default::default(),
             librdkafka_options: Default::default(),
             headers_key: None,
+            max_headers: None,
+            max_headers_bytes: None,
             acknowledgements: Default::default(),
         })
         .unwrap()
@@ -240,4 +346,43 @@ mod tests {
     fn generate_config() {
         KafkaSinkConfig::generate_config();
     }
+
+    #[test]
+    fn retries_are_bounded_by_default() {
+        let config = toml::from_str::<KafkaSinkConfig>(
+            r#"
+bootstrap_servers = "localhost:9092"
+topic = "topic-1234"
+encoding.codec = "json"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.request.retry_attempts, 5);
+
+        let config = toml::from_str::<KafkaSinkConfig>(
+            r#"
+bootstrap_servers = "localhost:9092"
+topic = "topic-1234"
+encoding.codec = "json"
+request.retry_initial_backoff_secs = 2
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.request.retry_attempts, 5);
+        assert_eq!(config.request.retry_initial_backoff_secs, 2);
+    }
+
+    #[test]
+    fn request_rejects_non_retry_options() {
+        let error = toml::from_str::<KafkaSinkConfig>(
+            r#"
+bootstrap_servers = "localhost:9092"
+topic = "topic-1234"
+encoding.codec = "json"
+request.concurrency = 10
+"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `concurrency`"));
+    }
 }
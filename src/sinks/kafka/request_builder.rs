@@ -1,14 +1,14 @@
-use std::num::NonZeroUsize;
+use std::{borrow::Cow, num::NonZeroUsize};
 
 use bytes::{Bytes, BytesMut};
-use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::message::{Header, Headers, OwnedHeaders};
 use tokio_util::codec::Encoder as _;
 use vector_core::config::LogSchema;
 
 use crate::{
     codecs::{Encoder, Transformer},
     event::{Event, Finalizable, Value},
-    internal_events::{KafkaHeaderExtractionError, TemplateRenderingError},
+    internal_events::{KafkaHeaderExtractionError, KafkaHeadersTruncated, TemplateRenderingError},
     sinks::{
         kafka::service::{KafkaRequest, KafkaRequestMetadata},
         util::metadata::RequestMetadataBuilder,
@@ -17,8 +17,10 @@ use crate::{
 };
 
 pub struct KafkaRequestBuilder {
-    pub key_field: Option<String>,
+    pub key_field: Option<Template>,
     pub headers_key: Option<String>,
+    pub max_headers: Option<usize>,
+    pub max_headers_bytes: Option<usize>,
     pub topic_template: Template,
     pub transformer: Transformer,
     pub encoder: Encoder<()>,
@@ -45,7 +47,12 @@ impl KafkaRequestBuilder {
             finalizers: event.take_finalizers(),
             key: get_key(&event, &self.key_field),
             timestamp_millis: get_timestamp_millis(&event, self.log_schema),
-            headers: get_headers(&event, &self.headers_key),
+            headers: get_headers(
+                &event,
+                &self.headers_key,
+                self.max_headers,
+                self.max_headers_bytes,
+            ),
             topic,
         };
         self.transformer.transform(&mut event);
@@ -64,16 +71,30 @@ impl KafkaRequestBuilder {
     }
 }
 
-fn get_key(event: &Event, key_field: &Option<String>) -> Option<Bytes> {
-    key_field.as_ref().and_then(|key_field| match event {
-        Event::Log(log) => log
-            .get(key_field.as_str())
-            .map(|value| value.coerce_to_bytes()),
-        Event::Metric(metric) => metric
-            .tags()
-            .and_then(|tags| tags.get(key_field))
-            .map(|value| value.to_owned().into()),
-        _ => None,
+fn get_key(event: &Event, key_field: &Option<Template>) -> Option<Bytes> {
+    key_field.as_ref().and_then(|key_field| {
+        if key_field.is_dynamic() {
+            return key_field
+                .render(event)
+                .map_err(|error| {
+                    emit!(TemplateRenderingError {
+                        field: Some("key_field"),
+                        drop_event: false,
+                        error,
+                    });
+                })
+                .ok();
+        }
+
+        let key_field = key_field.get_ref();
+        match event {
+            Event::Log(log) => log.get(key_field).map(|value| value.coerce_to_bytes()),
+            Event::Metric(metric) => metric
+                .tags()
+                .and_then(|tags| tags.get(key_field))
+                .map(|value| value.to_owned().into()),
+            _ => None,
+        }
     })
 }
 
@@ -89,24 +110,47 @@ fn get_timestamp_millis(event: &Event, log_schema: &'static LogSchema) -> Option
     .map(|ts| ts.timestamp_millis())
 }
 
-fn get_headers(event: &Event, headers_key: &Option<String>) -> Option<OwnedHeaders> {
+fn get_headers(
+    event: &Event,
+    headers_key: &Option<String>,
+    max_headers: Option<usize>,
+    max_headers_bytes: Option<usize>,
+) -> Option<OwnedHeaders> {
     headers_key.as_ref().and_then(|headers_key| {
         if let Event::Log(log) = event {
             if let Some(headers) = log.get(headers_key.as_str()) {
                 match headers {
                     Value::Object(headers_map) => {
                         let mut owned_headers = OwnedHeaders::new_with_capacity(headers_map.len());
+                        let mut headers_bytes = 0;
+                        let mut dropped = 0;
                         for (key, value) in headers_map {
-                            if let Value::Bytes(value_bytes) = value {
-                                owned_headers = owned_headers.insert(Header {
-                                    key,
-                                    value: Some(value_bytes.as_ref()),
-                                });
-                            } else {
-                                emit!(KafkaHeaderExtractionError {
-                                    header_field: headers_key
-                                });
+                            let value_bytes = match value {
+                                Value::Null => continue,
+                                Value::Bytes(value_bytes) => Cow::Borrowed(&value_bytes[..]),
+                                value => {
+                                    Cow::Owned(value.to_string_lossy().into_owned().into_bytes())
+                                }
+                            };
+                            let header_bytes = key.len() + value_bytes.len();
+                            if max_headers.map_or(false, |max| owned_headers.count() >= max)
+                                || max_headers_bytes
+                                    .map_or(false, |max| headers_bytes + header_bytes > max)
+                            {
+                                dropped += 1;
+                                continue;
                             }
+                            headers_bytes += header_bytes;
+                            owned_headers = owned_headers.insert(Header {
+                                key,
+                                value: Some(value_bytes.as_ref()),
+                            });
+                        }
+                        if dropped > 0 {
+                            emit!(KafkaHeadersTruncated {
+                                header_field: headers_key,
+                                dropped,
+                            });
                         }
                         return Some(owned_headers);
                     }
@@ -142,10 +186,90 @@ mod tests {
         let mut event = Event::Log(LogEvent::from("hello"));
         event.as_mut_log().insert(headers_key, header_values);
 
-        let headers = get_headers(&event, &Some(headers_key.to_string())).unwrap();
+        let headers = get_headers(&event, &Some(headers_key.to_string()), None, None).unwrap();
         assert_eq!(headers.get(0).key, "a-key");
         assert_eq!(headers.get(0).value.unwrap(), "a-value".as_bytes());
         assert_eq!(headers.get(1).key, "b-key");
         assert_eq!(headers.get(1).value.unwrap(), "b-value".as_bytes());
     }
+
+    #[test]
+    fn kafka_get_headers_stringified() {
+        let headers_key = "headers";
+        let mut header_values = BTreeMap::new();
+        header_values.insert("a-key".to_string(), Value::Bytes(Bytes::from("a-value")));
+        header_values.insert("b-key".to_string(), Value::Integer(42));
+        header_values.insert("c-key".to_string(), Value::Null);
+        header_values.insert("d-key".to_string(), Value::Boolean(true));
+
+        let mut event = Event::Log(LogEvent::from("hello"));
+        event.as_mut_log().insert(headers_key, header_values);
+
+        let headers = get_headers(&event, &Some(headers_key.to_string()), None, None).unwrap();
+        assert_eq!(headers.count(), 3);
+        assert_eq!(headers.get(0).key, "a-key");
+        assert_eq!(headers.get(0).value.unwrap(), "a-value".as_bytes());
+        assert_eq!(headers.get(1).key, "b-key");
+        assert_eq!(headers.get(1).value.unwrap(), "42".as_bytes());
+        assert_eq!(headers.get(2).key, "d-key");
+        assert_eq!(headers.get(2).value.unwrap(), "true".as_bytes());
+    }
+
+    #[test]
+    fn kafka_get_key_from_template() {
+        let key_field = Some(Template::try_from("{{ user_id }}").unwrap());
+
+        let mut event_1 = Event::Log(LogEvent::from("hello"));
+        event_1.as_mut_log().insert("user_id", "alice");
+        let mut event_2 = Event::Log(LogEvent::from("world"));
+        event_2.as_mut_log().insert("user_id", "alice");
+
+        let key_1 = get_key(&event_1, &key_field).unwrap();
+        let key_2 = get_key(&event_2, &key_field).unwrap();
+        assert_eq!(key_1, Bytes::from("alice"));
+        assert_eq!(key_1, key_2);
+
+        let event_3 = Event::Log(LogEvent::from("no user"));
+        assert_eq!(get_key(&event_3, &key_field), None);
+    }
+
+    #[test]
+    fn kafka_get_headers_truncated() {
+        crate::metrics::init_test();
+
+        let headers_dropped = || {
+            crate::metrics::Controller::get()
+                .unwrap()
+                .capture_metrics()
+                .into_iter()
+                .find(|metric| metric.name() == "kafka_headers_dropped_total")
+                .map_or(0.0, |metric| match metric.value() {
+                    crate::event::MetricValue::Counter { value } => *value,
+                    other => panic!("unexpected headers dropped metric: {:?}", other),
+                })
+        };
+        let before = headers_dropped();
+
+        let headers_key = "headers";
+        let mut header_values = BTreeMap::new();
+        header_values.insert("a-key".to_string(), Value::Bytes(Bytes::from("a-value")));
+        header_values.insert("b-key".to_string(), Value::Bytes(Bytes::from("b-value")));
+        header_values.insert("c-key".to_string(), Value::Bytes(Bytes::from("c-value")));
+
+        let mut event = Event::Log(LogEvent::from("hello"));
+        event.as_mut_log().insert(headers_key, header_values);
+
+        let headers_key = Some(headers_key.to_string());
+        let headers = get_headers(&event, &headers_key, Some(2), None).unwrap();
+        assert_eq!(headers.count(), 2);
+        assert_eq!(headers.get(0).key, "a-key");
+        assert_eq!(headers.get(1).key, "b-key");
+
+        // Each header is 12 bytes, so only the first fits.
+        let headers = get_headers(&event, &headers_key, None, Some(20)).unwrap();
+        assert_eq!(headers.count(), 1);
+        assert_eq!(headers.get(0).key, "a-key");
+
+        assert_eq!(headers_dropped() - before, 3.0);
+    }
 }
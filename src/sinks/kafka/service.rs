@@ -1,11 +1,15 @@
-use std::task::{Context, Poll};
+use std::{
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
 
 use bytes::Bytes;
 use futures::future::BoxFuture;
 use rdkafka::{
-    error::KafkaError,
+    client::ClientContext,
+    error::{KafkaError, RDKafkaErrorCode},
     message::OwnedHeaders,
-    producer::{FutureProducer, FutureRecord},
+    producer::{future_producer::OwnedDeliveryResult, FutureProducer, FutureRecord},
     util::Timeout,
 };
 use tower::Service;
@@ -19,15 +23,19 @@ use vector_core::{
 
 use crate::{
     event::{EventFinalizers, EventStatus, Finalizable},
+    internal_events::{KafkaMessageDelivered, KafkaSendAcknowledged},
     kafka::KafkaStatisticsContext,
+    sinks::util::retries::{RetryAction, RetryLogic},
 };
 
+#[derive(Clone)]
 pub struct KafkaRequest {
     pub body: Bytes,
     pub metadata: KafkaRequestMetadata,
     pub request_metadata: RequestMetadata,
 }
 
+#[derive(Clone)]
 pub struct KafkaRequestMetadata {
     pub finalizers: EventFinalizers,
     pub key: Option<Bytes>,
@@ -62,22 +70,102 @@ impl MetaDescriptive for KafkaRequest {
     }
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct KafkaRetryLogic;
+
+impl RetryLogic for KafkaRetryLogic {
+    type Error = KafkaError;
+    type Response = KafkaResponse;
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match retry_action(error) {
+            RetryAction::Retry(reason) => {
+                debug!(message = "Kafka error is retriable.", %error, %reason);
+                true
+            }
+            RetryAction::DontRetry(reason) => {
+                debug!(message = "Kafka error is not retriable.", %error, %reason);
+                false
+            }
+            RetryAction::Successful => false,
+        }
+    }
+}
+
+fn retry_action(error: &KafkaError) -> RetryAction {
+    let code = match error {
+        KafkaError::ClientConfig(..) | KafkaError::ClientCreation(_) => {
+            return RetryAction::DontRetry("the client is misconfigured".into())
+        }
+        KafkaError::MessageProduction(code) | KafkaError::Global(code) => *code,
+        _ => return RetryAction::DontRetry("unexpected error".into()),
+    };
+    match code {
+        RDKafkaErrorCode::QueueFull => RetryAction::Retry("the producer queue is full".into()),
+        RDKafkaErrorCode::MessageTimedOut | RDKafkaErrorCode::RequestTimedOut => {
+            RetryAction::Retry("the send timed out".into())
+        }
+        RDKafkaErrorCode::BrokerTransportFailure
+        | RDKafkaErrorCode::AllBrokersDown
+        | RDKafkaErrorCode::NetworkException => {
+            RetryAction::Retry("the brokers could not be reached".into())
+        }
+        RDKafkaErrorCode::LeaderNotAvailable | RDKafkaErrorCode::NotLeaderForPartition => {
+            RetryAction::Retry("the partition leader is changing".into())
+        }
+        RDKafkaErrorCode::MessageSizeTooLarge | RDKafkaErrorCode::InvalidMessageSize => {
+            RetryAction::DontRetry("the message is too large".into())
+        }
+        _ => RetryAction::DontRetry("unexpected error".into()),
+    }
+}
+
+/// A producer capable of sending records to Kafka.
+///
+/// This only exists so the service can be exercised without a broker.
+pub trait KafkaProducer: Clone + Send + Sync + 'static {
+    fn send<'a>(
+        &'a self,
+        record: FutureRecord<'a, [u8], [u8]>,
+        queue_timeout: Timeout,
+    ) -> BoxFuture<'a, OwnedDeliveryResult>;
+}
+
+impl<C: ClientContext + 'static> KafkaProducer for FutureProducer<C> {
+    fn send<'a>(
+        &'a self,
+        record: FutureRecord<'a, [u8], [u8]>,
+        queue_timeout: Timeout,
+    ) -> BoxFuture<'a, OwnedDeliveryResult> {
+        // unless a timeout is given, rdkafka will internally retry forever if the queue is full
+        Box::pin(FutureProducer::send(self, record, queue_timeout))
+    }
+}
+
 #[derive(Clone)]
-pub struct KafkaService {
-    kafka_producer: FutureProducer<KafkaStatisticsContext>,
+pub struct KafkaService<P = FutureProducer<KafkaStatisticsContext>> {
+    kafka_producer: P,
     bytes_sent: Registered<BytesSent>,
+    slow_send_threshold: Option<Duration>,
+    send_timeout: Option<Duration>,
 }
 
-impl KafkaService {
-    pub(crate) fn new(kafka_producer: FutureProducer<KafkaStatisticsContext>) -> KafkaService {
+impl<P: KafkaProducer> KafkaService<P> {
+    pub(crate) fn new(
+        kafka_producer: P,
+        slow_send_threshold: Option<Duration>,
+        send_timeout: Option<Duration>,
+    ) -> Self {
         KafkaService {
             kafka_producer,
             bytes_sent: register!(BytesSent::from(Protocol("kafka".into()))),
+            slow_send_threshold,
+            send_timeout,
         }
     }
 }
 
-impl Service<KafkaRequest> for KafkaService {
+impl<P: KafkaProducer> Service<KafkaRequest> for KafkaService<P> {
     type Response = KafkaResponse;
     type Error = KafkaError;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
@@ -92,8 +180,8 @@ impl Service<KafkaRequest> for KafkaService {
         Box::pin(async move {
             let event_byte_size = request.get_metadata().events_byte_size();
 
-            let mut record =
-                FutureRecord::to(&request.metadata.topic).payload(request.body.as_ref());
+            let mut record: FutureRecord<'_, [u8], [u8]> =
+                FutureRecord::to(&request.metadata.topic).payload(&request.body[..]);
             if let Some(key) = &request.metadata.key {
                 record = record.key(&key[..]);
             }
@@ -104,9 +192,30 @@ impl Service<KafkaRequest> for KafkaService {
                 record = record.headers(headers);
             }
 
-            // rdkafka will internally retry forever if the queue is full
-            match this.kafka_producer.send(record, Timeout::Never).await {
-                Ok((_partition, _offset)) => {
+            let start = Instant::now();
+            let queue_timeout = this.send_timeout.map_or(Timeout::Never, Timeout::After);
+            let send = this.kafka_producer.send(record, queue_timeout);
+            let result = match this.send_timeout {
+                Some(send_timeout) => match tokio::time::timeout(send_timeout, send).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        return Err(KafkaError::MessageProduction(
+                            RDKafkaErrorCode::MessageTimedOut,
+                        ))
+                    }
+                },
+                None => send.await,
+            };
+            match result {
+                Ok((partition, _offset)) => {
+                    emit!(KafkaSendAcknowledged {
+                        latency: start.elapsed(),
+                        slow_send_threshold: this.slow_send_threshold,
+                    });
+                    emit!(KafkaMessageDelivered {
+                        topic: &request.metadata.topic,
+                        partition,
+                    });
                     this.bytes_sent.emit(ByteSize(
                         request.body.len() + request.metadata.key.map(|x| x.len()).unwrap_or(0),
                     ));
@@ -117,3 +226,176 @@ impl Service<KafkaRequest> for KafkaService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tower::ServiceExt;
+    use vector_core::event::{Metric, MetricValue};
+
+    use super::*;
+
+    /// A producer that acknowledges every record after a fixed delay.
+    #[derive(Clone)]
+    struct DelayedProducer {
+        delay: Duration,
+        partition: i32,
+    }
+
+    impl KafkaProducer for DelayedProducer {
+        fn send<'a>(
+            &'a self,
+            _record: FutureRecord<'a, [u8], [u8]>,
+            _queue_timeout: Timeout,
+        ) -> BoxFuture<'a, OwnedDeliveryResult> {
+            let (delay, partition) = (self.delay, self.partition);
+            Box::pin(async move {
+                tokio::time::sleep(delay).await;
+                Ok((partition, 0))
+            })
+        }
+    }
+
+    fn request() -> KafkaRequest {
+        KafkaRequest {
+            body: Bytes::from("hello"),
+            metadata: KafkaRequestMetadata {
+                finalizers: EventFinalizers::default(),
+                key: None,
+                timestamp_millis: None,
+                headers: None,
+                topic: "topic".to_owned(),
+            },
+            request_metadata: RequestMetadata::default(),
+        }
+    }
+
+    /// Captures the current metrics by name.
+    ///
+    /// Other tests may record the same metrics, so tests compare against the values captured
+    /// before acting rather than asserting absolute values.
+    fn capture_metrics() -> HashMap<String, Metric> {
+        crate::metrics::Controller::get()
+            .unwrap()
+            .capture_metrics()
+            .into_iter()
+            .map(|metric| (metric.name().to_string(), metric))
+            .collect()
+    }
+
+    fn counter_value(metrics: &HashMap<String, Metric>, name: &str) -> f64 {
+        match metrics.get(name).map(Metric::value) {
+            Some(MetricValue::Counter { value }) => *value,
+            None => 0.0,
+            other => panic!("unexpected {} metric: {:?}", name, other),
+        }
+    }
+
+    /// The count and sum of a histogram.
+    fn histogram_value(metrics: &HashMap<String, Metric>, name: &str) -> (u64, f64) {
+        match metrics.get(name).map(Metric::value) {
+            Some(MetricValue::AggregatedHistogram { count, sum, .. }) => (*count, *sum),
+            None => (0, 0.0),
+            other => panic!("unexpected {} metric: {:?}", name, other),
+        }
+    }
+
+    #[tokio::test]
+    async fn records_slow_sends() {
+        crate::metrics::init_test();
+        let before = capture_metrics();
+
+        let producer = DelayedProducer {
+            delay: Duration::from_millis(50),
+            partition: 0,
+        };
+        let service = KafkaService::new(producer, Some(Duration::from_millis(10)), None);
+        service.clone().oneshot(request()).await.unwrap();
+        service.oneshot(request()).await.unwrap();
+
+        let after = capture_metrics();
+        let (count_before, sum_before) = histogram_value(&before, "kafka_send_latency_seconds");
+        let (count_after, sum_after) = histogram_value(&after, "kafka_send_latency_seconds");
+        assert_eq!(count_after - count_before, 2);
+        assert!(sum_after - sum_before >= 0.1);
+        assert_eq!(
+            counter_value(&after, "kafka_slow_sends_total")
+                - counter_value(&before, "kafka_slow_sends_total"),
+            2.0
+        );
+    }
+
+    #[test]
+    fn classifies_retriable_errors() {
+        let logic = KafkaRetryLogic;
+        for code in [
+            RDKafkaErrorCode::QueueFull,
+            RDKafkaErrorCode::MessageTimedOut,
+            RDKafkaErrorCode::BrokerTransportFailure,
+            RDKafkaErrorCode::AllBrokersDown,
+            RDKafkaErrorCode::LeaderNotAvailable,
+            RDKafkaErrorCode::NotLeaderForPartition,
+        ] {
+            assert!(logic.is_retriable_error(&KafkaError::MessageProduction(code)));
+        }
+        assert!(logic.is_retriable_error(&KafkaError::Global(RDKafkaErrorCode::AllBrokersDown)));
+
+        for code in [
+            RDKafkaErrorCode::MessageSizeTooLarge,
+            RDKafkaErrorCode::InvalidMessageSize,
+            RDKafkaErrorCode::TopicAuthorizationFailed,
+        ] {
+            assert!(!logic.is_retriable_error(&KafkaError::MessageProduction(code)));
+        }
+        assert!(!logic.is_retriable_error(&KafkaError::ClientCreation("invalid config".to_owned())));
+        assert!(!logic.is_retriable_error(&KafkaError::Canceled));
+    }
+
+    #[tokio::test]
+    async fn times_out_sends() {
+        let producer = DelayedProducer {
+            delay: Duration::from_secs(3600),
+            partition: 0,
+        };
+        let service = KafkaService::new(producer, None, Some(Duration::from_millis(10)));
+
+        let error = service.oneshot(request()).await.err().unwrap();
+        assert!(matches!(
+            error,
+            KafkaError::MessageProduction(RDKafkaErrorCode::MessageTimedOut)
+        ));
+    }
+
+    #[tokio::test]
+    async fn records_delivered_partition() {
+        crate::metrics::init_test();
+
+        let delivered = || {
+            crate::metrics::Controller::get()
+                .unwrap()
+                .capture_metrics()
+                .into_iter()
+                .find(|metric| {
+                    metric.name() == "kafka_delivered_messages_total"
+                        && metric.tags().map_or(false, |tags| {
+                            tags.get("topic") == Some("topic") && tags.get("partition") == Some("3")
+                        })
+                })
+                .map_or(0.0, |metric| match metric.value() {
+                    MetricValue::Counter { value } => *value,
+                    other => panic!("unexpected delivered metric: {:?}", other),
+                })
+        };
+        let before = delivered();
+
+        let producer = DelayedProducer {
+            delay: Duration::ZERO,
+            partition: 3,
+        };
+        let service = KafkaService::new(producer, None, None);
+        service.oneshot(request()).await.unwrap();
+
+        assert_eq!(delivered() - before, 1.0);
+    }
+}
@@ -10,7 +10,7 @@ use rdkafka::{
 };
 use snafu::{ResultExt, Snafu};
 use tokio::time::Duration;
-use tower::limit::ConcurrencyLimit;
+use tower::ServiceBuilder;
 use vector_core::config::log_schema;
 
 use super::config::{KafkaRole, KafkaSinkConfig};
@@ -20,8 +20,9 @@ use crate::{
     kafka::KafkaStatisticsContext,
     sinks::{
         kafka::{
-            config::QUEUED_MIN_MESSAGES, request_builder::KafkaRequestBuilder,
-            service::KafkaService,
+            config::{KafkaRetryConfig, QUEUED_MIN_MESSAGES},
+            request_builder::KafkaRequestBuilder,
+            service::{KafkaRetryLogic, KafkaService},
         },
         util::{builder::SinkBuilderExt, StreamSink},
     },
@@ -34,15 +35,20 @@ pub(super) enum BuildError {
     KafkaCreateFailed { source: KafkaError },
     #[snafu(display("invalid topic template: {}", source))]
     TopicTemplate { source: TemplateParseError },
+    #[snafu(display("invalid key field template: {}", source))]
+    KeyFieldTemplate { source: TemplateParseError },
 }
 
 pub struct KafkaSink {
     transformer: Transformer,
     encoder: Encoder<()>,
     service: KafkaService,
+    request: KafkaRetryConfig,
     topic: Template,
-    key_field: Option<String>,
+    key_field: Option<Template>,
     headers_key: Option<String>,
+    max_headers: Option<usize>,
+    max_headers_bytes: Option<usize>,
 }
 
 pub(crate) fn create_producer(
@@ -64,20 +70,37 @@ impl KafkaSink {
 
         Ok(KafkaSink {
             headers_key: config.headers_key,
+            max_headers: config.max_headers,
+            max_headers_bytes: config.max_headers_bytes,
             transformer,
             encoder,
-            service: KafkaService::new(producer),
+            service: KafkaService::new(
+                producer,
+                config.slow_send_threshold_ms.map(Duration::from_millis),
+                config.send_timeout_ms.map(Duration::from_millis),
+            ),
+            request: config.request,
             topic: Template::try_from(config.topic).context(TopicTemplateSnafu)?,
-            key_field: config.key_field,
+            key_field: config
+                .key_field
+                .map(Template::try_from)
+                .transpose()
+                .context(KeyFieldTemplateSnafu)?,
         })
     }
 
     async fn run_inner(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
         // rdkafka will internally retry forever, so we need some limit to prevent this from overflowing
-        let service = ConcurrencyLimit::new(self.service, QUEUED_MIN_MESSAGES as usize);
+        let service = ServiceBuilder::new()
+            .concurrency_limit(QUEUED_MIN_MESSAGES as usize)
+            .retry(self.request.retry_policy(KafkaRetryLogic))
+            .map_err(crate::Error::from)
+            .service(self.service);
         let mut request_builder = KafkaRequestBuilder {
             key_field: self.key_field,
             headers_key: self.headers_key,
+            max_headers: self.max_headers,
+            max_headers_bytes: self.max_headers_bytes,
             topic_template: self.topic,
             transformer: self.transformer,
             encoder: self.encoder,
@@ -63,8 +63,13 @@ mod integration_test {
             auth: KafkaAuthConfig::default(),
             socket_timeout_ms: 60000,
             message_timeout_ms: 300000,
+            slow_send_threshold_ms: None,
+            send_timeout_ms: None,
+            request: Default::default(),
             librdkafka_options: HashMap::new(),
             headers_key: None,
+            max_headers: None,
+            max_headers_bytes: None,
             acknowledgements: Default::default(),
         };
         self::sink::healthcheck(config).await.unwrap();
@@ -117,9 +122,14 @@ mod integration_test {
             },
             socket_timeout_ms: 60000,
             message_timeout_ms: 300000,
+            slow_send_threshold_ms: None,
+            send_timeout_ms: None,
+            request: Default::default(),
             batch,
             librdkafka_options,
             headers_key: None,
+            max_headers: None,
+            max_headers_bytes: None,
             acknowledgements: Default::default(),
         };
         config.clone().to_rdkafka(KafkaRole::Consumer)?;
@@ -249,8 +259,13 @@ mod integration_test {
             auth: kafka_auth.clone(),
             socket_timeout_ms: 60000,
             message_timeout_ms: 300000,
+            slow_send_threshold_ms: None,
+            send_timeout_ms: None,
+            request: Default::default(),
             librdkafka_options: HashMap::new(),
             headers_key: Some(headers_key.clone()),
+            max_headers: None,
+            max_headers_bytes: None,
             acknowledgements: Default::default(),
         };
         let topic = format!("{}-{}", topic, chrono::Utc::now().format("%Y%m%d"));
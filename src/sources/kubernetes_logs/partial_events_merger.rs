@@ -24,7 +24,7 @@ pub fn build(enabled: bool, log_namespace: LogNamespace) -> PartialEventsMerger
 
         // Merge the message field of each event by concatenating it, with a space delimiter.
         let mut merge_strategies = IndexMap::new();
-        merge_strategies.insert(key, MergeStrategy::ConcatRaw);
+        merge_strategies.insert(key, MergeStrategy::ConcatRaw.into());
 
         // Group events by their file.
         let group_by = vec![FILE_KEY.to_string()];
@@ -51,7 +51,10 @@ use crate::{
     source_sender::CHUNK_SIZE,
     spawn_named,
     topology::task::TaskError,
-    transforms::{SyncTransform, TaskTransform, Transform, TransformOutputs, TransformOutputsBuf},
+    transforms::{
+        MultiOutputTaskTransform, SyncTransform, TaskTransform, Transform, TransformOutputs,
+        TransformOutputsBuf,
+    },
     utilization::wrap,
     SourceSender,
 };
@@ -652,6 +655,7 @@ fn build_transform(
             node.typetag,
             &node.key,
         ),
+        Transform::MultiOutputTask(t) => build_multi_output_task_transform(t, node, input_rx),
     }
 }
 
@@ -888,3 +892,51 @@ fn build_task_transform(
 
     (task, outputs)
 }
+
+fn build_multi_output_task_transform(
+    t: Box<dyn MultiOutputTaskTransform>,
+    node: TransformNode,
+    input_rx: BufferReceiver<EventArray>,
+) -> (Task, HashMap<OutputId, fanout::ControlChannel>) {
+    let (mut outputs, controls) = TransformOutputs::new(node.outputs);
+
+    let input_rx = crate::utilization::wrap(input_rx.into_stream());
+
+    let input_type = node.input_details.data_type();
+    let events_received = register!(EventsReceived);
+    let filtered = input_rx
+        .filter(move |events| ready(filter_events_type(events, input_type)))
+        .inspect(move |events| {
+            events_received.emit(CountByteSize(
+                events.len(),
+                events.estimated_json_encoded_size_of(),
+            ))
+        });
+    let mut stream = t.transform(Box::pin(filtered));
+    let transform = async move {
+        debug!("Multi-output task transform starting.");
+
+        while let Some(mut outputs_buf) = stream.next().await {
+            if let Err(e) = outputs.send(&mut outputs_buf).await {
+                debug!("Multi-output task transform finished with an error.");
+                return Err(TaskError::wrapped(e));
+            }
+        }
+
+        debug!("Multi-output task transform finished normally.");
+        Ok(TaskOutput::Transform)
+    }
+    .boxed();
+
+    let mut output_controls = HashMap::new();
+    for (name, control) in controls {
+        let id = name
+            .map(|name| OutputId::from((&node.key, name)))
+            .unwrap_or_else(|| OutputId::from(&node.key));
+        output_controls.insert(id, control);
+    }
+
+    let task = Task::new(node.key.clone(), node.typetag, transform);
+
+    (task, output_controls)
+}
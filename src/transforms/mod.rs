@@ -32,8 +32,8 @@ pub mod throttle;
 
 use vector_config::{configurable_component, NamedComponent};
 pub use vector_core::transform::{
-    FunctionTransform, OutputBuffer, SyncTransform, TaskTransform, Transform, TransformOutputs,
-    TransformOutputsBuf,
+    FunctionTransform, MultiOutputTaskTransform, OutputBuffer, SyncTransform, TaskTransform,
+    Transform, TransformOutputs, TransformOutputsBuf,
 };
 use vector_core::{
     config::{Input, LogNamespace, Output},
@@ -220,4 +220,44 @@ mod test {
 
         (topology, rx)
     }
+
+    /// Like [`create_topology`], but also returns the events sent to the named `output` of the
+    /// transform.
+    #[allow(dead_code)]
+    pub async fn create_topology_with_named_output<T: Into<Transforms>>(
+        events: impl Stream<Item = Event> + Send + 'static,
+        transform_config: T,
+        output: &str,
+    ) -> (
+        RunningTopology,
+        mpsc::Receiver<Event>,
+        mpsc::Receiver<Event>,
+    ) {
+        let mut builder = ConfigBuilder::default();
+
+        let (tx, rx) = mpsc::channel(1);
+        let (named_tx, named_rx) = mpsc::channel(1);
+
+        builder.add_source("in", UnitTestStreamSourceConfig::new(events));
+        builder.add_transform("transform", &["in"], transform_config);
+        builder.add_sink(
+            "out",
+            &["transform"],
+            UnitTestStreamSinkConfig::new(
+                PollSender::new(tx).sink_map_err(|error| panic!("{}", error)),
+            ),
+        );
+        builder.add_sink(
+            "named_out",
+            &[format!("transform.{}", output).as_str()],
+            UnitTestStreamSinkConfig::new(
+                PollSender::new(named_tx).sink_map_err(|error| panic!("{}", error)),
+            ),
+        );
+
+        let config = builder.build().expect("building config should not fail");
+        let (topology, _) = start_topology(config, false).await;
+
+        (topology, rx, named_rx)
+    }
 }
@@ -0,0 +1,38 @@
+use std::fmt;
+
+use bloom::{BloomFilter, ASMS};
+use bytes::Bytes;
+
+/// The number of bits a filter sets aside for each distinct value it is expected to hold.
+///
+/// At 10 bits per value, a filter holding that many values has a false positive rate of about 1%.
+const BITS_PER_VALUE: usize = 10;
+
+/// Probabilistic set of the dedup values already combined into a reduce state.
+pub(super) struct DedupFilter {
+    bloom: BloomFilter,
+}
+
+impl fmt::Debug for DedupFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupFilter").finish_non_exhaustive()
+    }
+}
+
+impl DedupFilter {
+    pub(super) fn new(cache_size: usize) -> Self {
+        let num_bits = cache_size * 8;
+        let expected_values = u32::try_from(num_bits / BITS_PER_VALUE)
+            .unwrap_or(u32::MAX)
+            .max(1);
+        let num_hashes = bloom::optimal_num_hashes(num_bits, expected_values);
+        Self {
+            bloom: BloomFilter::with_size(num_bits, num_hashes),
+        }
+    }
+
+    /// Records the value, returning whether it had (probably) been seen before.
+    pub(super) fn check_and_insert(&mut self, value: &Bytes) -> bool {
+        !self.bloom.insert(value)
+    }
+}
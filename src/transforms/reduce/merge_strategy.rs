@@ -1,7 +1,11 @@
-use std::collections::HashSet;
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
 
 use bytes::{Bytes, BytesMut};
 use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
 use ordered_float::NotNan;
 use vector_config::configurable_component;
 
@@ -20,18 +24,52 @@ pub enum MergeStrategy {
     /// Works as a way to coalesce by not retaining `null`.
     Retain,
 
+    /// Keep the first and the last value seen, as the `first` and `last` fields of an object.
+    ///
+    /// This works with values of any type, and is useful to track how a field changed over the
+    /// combined events.
+    FirstLast,
+
     /// Sum all numeric values.
     Sum,
 
+    /// Count the values seen.
+    ///
+    /// The result is the number of events that had the field, whatever its values were.
+    Count,
+
     /// Keep the maximum numeric value seen.
     Max,
 
     /// Keep the minimum numeric value seen.
     Min,
 
+    /// Keep the mean of all numeric values seen.
+    ///
+    /// The result is always a floating-point number.
+    Average,
+
+    /// Keep the population variance of all numeric values seen.
+    ///
+    /// The variance is computed as values are received, without keeping them. A single value has a
+    /// variance of `0`. The result is always a floating-point number.
+    Variance,
+
+    /// Keep the population standard deviation of all numeric values seen.
+    ///
+    /// The standard deviation is computed as values are received, without keeping them. A single
+    /// value has a standard deviation of `0`. The result is always a floating-point number.
+    StdDev,
+
     /// Append each value to an array.
     Array,
 
+    /// Append each value to an array, unless an equal value is already present.
+    ///
+    /// Values are kept in the order they were first seen. Objects and arrays are compared by
+    /// structural equality and are not flattened.
+    Set,
+
     /// Concatenate each string value, delimited with a space.
     Concat,
 
@@ -51,6 +89,166 @@ pub enum MergeStrategy {
     FlatUnique,
 }
 
+impl MergeStrategy {
+    /// Gets the configuration name of this strategy.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Discard => "discard",
+            Self::Retain => "retain",
+            Self::FirstLast => "first_last",
+            Self::Sum => "sum",
+            Self::Count => "count",
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::Average => "average",
+            Self::Variance => "variance",
+            Self::StdDev => "std_dev",
+            Self::Array => "array",
+            Self::Set => "set",
+            Self::Concat => "concat",
+            Self::ConcatNewline => "concat_newline",
+            Self::ConcatRaw => "concat_raw",
+            Self::ShortestArray => "shortest_array",
+            Self::LongestArray => "longest_array",
+            Self::FlatUnique => "flat_unique",
+        }
+    }
+}
+
+/// A merge strategy, given either by name or as a set of options.
+///
+/// Most strategies are given by name:
+///
+/// ```toml
+/// merge_strategies.message = "concat"
+/// ```
+///
+/// Strategies that take options are given with an enum-style notation:
+///
+/// ```toml
+/// merge_strategies.message = { type = "concat", separator = ", " }
+/// merge_strategies.items = { type = "array_merge_by_key", key = "id" }
+/// ```
+#[configurable_component]
+#[derive(Clone, Debug)]
+#[serde(untagged)]
+pub enum AnyMergeStrategy {
+    /// A merge strategy given by name.
+    Named(#[configurable(derived)] MergeStrategy),
+
+    /// A merge strategy given with options.
+    Options(#[configurable(derived)] MergeStrategyOptions),
+}
+
+impl AnyMergeStrategy {
+    /// Gets the configuration name of this strategy.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Named(strategy) => strategy.as_str(),
+            Self::Options(options) => options.as_str(),
+        }
+    }
+
+    /// Whether fields merged with this strategy can be given a TTL.
+    ///
+    /// This also holds for the options form of a strategy in [`TTL_MERGE_STRATEGIES`].
+    pub fn supports_ttl(&self) -> bool {
+        TTL_MERGE_STRATEGIES
+            .iter()
+            .any(|strategy| strategy.as_str() == self.as_str())
+    }
+}
+
+/// The strategies that fields with a TTL can be merged with.
+///
+/// An expiring field keeps each of its values until they expire, which is only worth it for
+/// strategies whose result holds every value anyway. `retain` is also supported, as it only needs
+/// the latest value.
+pub(crate) const TTL_MERGE_STRATEGIES: [MergeStrategy; 5] = [
+    MergeStrategy::Array,
+    MergeStrategy::Concat,
+    MergeStrategy::ConcatNewline,
+    MergeStrategy::ConcatRaw,
+    MergeStrategy::Retain,
+];
+
+/// Merge strategies selected by the value of a field.
+///
+/// For example, to sum `value` for events with a `type` of `metric` but keep the last `value` for
+/// events with a `type` of `event`:
+///
+/// ```toml
+/// [conditional_merge_strategies]
+/// field = "type"
+/// values.metric.value = "sum"
+/// values.event.value = "retain"
+/// ```
+#[configurable_component]
+#[derive(Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConditionalMergeStrategies {
+    /// The field whose value selects the merge strategies for an event.
+    ///
+    /// Events are also grouped by this field, as if it were listed in `group_by`, so events with
+    /// different values are never combined together and every combined event is merged with a
+    /// single set of strategies.
+    #[configurable(metadata(docs::examples = "type"))]
+    pub field: String,
+
+    /// A map of values of `field` to the merge strategies used for events with that value.
+    ///
+    /// These take precedence over `merge_strategies` for the fields they name. Events whose value
+    /// is not listed, or that do not have the field, use `merge_strategies`.
+    #[configurable(metadata(
+        docs::additional_props_description = "The merge strategies for events with this value."
+    ))]
+    pub values: IndexMap<String, IndexMap<String, AnyMergeStrategy>>,
+}
+
+/// Merge strategies that take options.
+#[configurable_component]
+#[derive(Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MergeStrategyOptions {
+    /// Concatenate each string value, delimited with a separator.
+    Concat {
+        /// The separator placed between each concatenated value.
+        separator: String,
+    },
+
+    /// Merge arrays of objects, combining objects that share the same value for a key.
+    ///
+    /// Objects from later events are merged into earlier objects with the same key, with fields from
+    /// the later object taking precedence. Objects without the key, and elements that are not
+    /// objects, are appended.
+    ArrayMergeByKey {
+        /// The field of each object whose value identifies the objects to merge.
+        key: String,
+    },
+}
+
+impl MergeStrategyOptions {
+    /// Gets the configuration name of this strategy.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Concat { .. } => "concat",
+            Self::ArrayMergeByKey { .. } => "array_merge_by_key",
+        }
+    }
+}
+
+impl From<MergeStrategy> for AnyMergeStrategy {
+    fn from(strategy: MergeStrategy) -> Self {
+        Self::Named(strategy)
+    }
+}
+
+impl From<MergeStrategyOptions> for AnyMergeStrategy {
+    fn from(options: MergeStrategyOptions) -> Self {
+        Self::Options(options)
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DiscardMerger {
     v: Value,
@@ -99,6 +297,39 @@ impl ReduceValueMerger for RetainMerger {
     }
 }
 
+#[derive(Debug, Clone)]
+struct FirstLastMerger {
+    first: Value,
+    last: Value,
+}
+
+impl FirstLastMerger {
+    fn new(v: Value) -> Self {
+        Self {
+            first: v.clone(),
+            last: v,
+        }
+    }
+}
+
+impl ReduceValueMerger for FirstLastMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        self.last = v;
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(
+            k.as_str(),
+            Value::Object(BTreeMap::from([
+                ("first".to_owned(), self.first),
+                ("last".to_owned(), self.last),
+            ])),
+        );
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ConcatMerger {
     v: BytesMut,
@@ -106,9 +337,8 @@ struct ConcatMerger {
 }
 
 impl ConcatMerger {
-    fn new(v: Bytes, join_by: Option<char>) -> Self {
-        // We need to get the resulting bytes for this character in case it's actually a multi-byte character.
-        let join_by = join_by.map(|c| c.to_string().into_bytes());
+    fn new(v: Bytes, join_by: Option<&str>) -> Self {
+        let join_by = join_by.map(|s| s.as_bytes().to_vec());
 
         Self {
             v: BytesMut::from(&v[..]),
@@ -189,6 +419,36 @@ impl ReduceValueMerger for ArrayMerger {
     }
 }
 
+#[derive(Debug, Clone)]
+struct SetMerger {
+    v: Vec<Value>,
+    // Values already in `v`, so that each add is a single lookup. `v` only keeps their order.
+    seen: HashSet<Value>,
+}
+
+impl SetMerger {
+    #[allow(clippy::mutable_key_type)] // false positive due to bytes::Bytes
+    fn new(v: Value) -> Self {
+        let mut seen = HashSet::default();
+        seen.insert(v.clone());
+        Self { v: vec![v], seen }
+    }
+}
+
+impl ReduceValueMerger for SetMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        if self.seen.insert(v.clone()) {
+            self.v.push(v);
+        }
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k.as_str(), Value::Array(self.v));
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct LongestArrayMerger {
     v: Vec<Value>,
@@ -221,6 +481,62 @@ impl ReduceValueMerger for LongestArrayMerger {
     }
 }
 
+#[derive(Debug, Clone)]
+struct ArrayMergeByKeyMerger {
+    key: String,
+    v: Vec<Value>,
+}
+
+impl ArrayMergeByKeyMerger {
+    fn new(key: String, v: Vec<Value>) -> Self {
+        let mut merger = Self { key, v: Vec::new() };
+        merger.upsert_all(v);
+        merger
+    }
+
+    fn upsert_all(&mut self, values: Vec<Value>) {
+        for value in values {
+            let position = match &value {
+                Value::Object(object) => object.get(&self.key).and_then(|key| {
+                    self.v.iter().position(|element| match element {
+                        Value::Object(existing) => existing.get(&self.key) == Some(key),
+                        _ => false,
+                    })
+                }),
+                _ => None,
+            };
+
+            match (position, value) {
+                (Some(i), Value::Object(object)) => {
+                    if let Value::Object(existing) = &mut self.v[i] {
+                        existing.extend(object);
+                    }
+                }
+                (_, value) => self.v.push(value),
+            }
+        }
+    }
+}
+
+impl ReduceValueMerger for ArrayMergeByKeyMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        if let Value::Array(a) = v {
+            self.upsert_all(a);
+            Ok(())
+        } else {
+            Err(format!(
+                "expected array value, found: '{}'",
+                v.to_string_lossy()
+            ))
+        }
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k.as_str(), Value::Array(self.v));
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ShortestArrayMerger {
     v: Vec<Value>,
@@ -302,13 +618,15 @@ impl ReduceValueMerger for FlatUniqueMerger {
 struct TimestampWindowMerger {
     started: DateTime<Utc>,
     latest: DateTime<Utc>,
+    end_suffix: String,
 }
 
 impl TimestampWindowMerger {
-    const fn new(v: DateTime<Utc>) -> Self {
+    fn new(v: DateTime<Utc>, end_suffix: &str) -> Self {
         Self {
             started: v,
             latest: v,
+            end_suffix: end_suffix.to_owned(),
         }
     }
 }
@@ -327,7 +645,10 @@ impl ReduceValueMerger for TimestampWindowMerger {
     }
 
     fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
-        v.insert(format!("{}_end", k).as_str(), Value::Timestamp(self.latest));
+        v.insert(
+            format!("{}{}", k, self.end_suffix).as_str(),
+            Value::Timestamp(self.latest),
+        );
         v.insert(k.as_str(), Value::Timestamp(self.started));
         Ok(())
     }
@@ -514,24 +835,242 @@ impl ReduceValueMerger for MinNumberMerger {
     }
 }
 
+#[derive(Debug, Clone)]
+struct AverageNumberMerger {
+    sum: f64,
+    count: usize,
+}
+
+impl AverageNumberMerger {
+    const fn new(v: f64) -> Self {
+        Self { sum: v, count: 1 }
+    }
+}
+
+impl ReduceValueMerger for AverageNumberMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        match v {
+            Value::Integer(i) => self.sum += i as f64,
+            Value::Float(f) => self.sum += f.into_inner(),
+            _ => {
+                return Err(format!(
+                    "expected numeric value, found: '{}'",
+                    v.to_string_lossy()
+                ));
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        let mean = self.sum / self.count as f64;
+        v.insert(k.as_str(), Value::from_f64_or_zero(mean));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CountMerger {
+    count: i64,
+}
+
+impl CountMerger {
+    const fn new() -> Self {
+        Self { count: 1 }
+    }
+}
+
+impl ReduceValueMerger for CountMerger {
+    fn add(&mut self, _v: Value) -> Result<(), String> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k.as_str(), Value::Integer(self.count));
+        Ok(())
+    }
+}
+
+/// Tracks the variance of numeric values using Welford's online algorithm.
+#[derive(Debug, Clone)]
+struct VarianceMerger {
+    count: usize,
+    mean: f64,
+    // The sum of squared differences from the current mean.
+    m2: f64,
+    std_dev: bool,
+}
+
+impl VarianceMerger {
+    const fn new(v: f64, std_dev: bool) -> Self {
+        Self {
+            count: 1,
+            mean: v,
+            m2: 0.0,
+            std_dev,
+        }
+    }
+}
+
+impl ReduceValueMerger for VarianceMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        let v = match v {
+            Value::Integer(i) => i as f64,
+            Value::Float(f) => f.into_inner(),
+            _ => {
+                return Err(format!(
+                    "expected numeric value, found: '{}'",
+                    v.to_string_lossy()
+                ));
+            }
+        };
+        self.count += 1;
+        let delta = v - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (v - self.mean);
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        let variance = self.m2 / self.count as f64;
+        let result = if self.std_dev {
+            variance.sqrt()
+        } else {
+            variance
+        };
+        v.insert(k.as_str(), Value::from_f64_or_zero(result));
+        Ok(())
+    }
+}
+
+/// Wraps a merge strategy so that only values received within `ttl` contribute to the merged
+/// result.
+///
+/// Each value is kept alongside the time it was received, and expired values are evicted whenever
+/// a new value is added and again at flush time. The wrapped strategy is only applied to the
+/// remaining values when the field is inserted into the flushed event. Only strategies that
+/// [support a TTL](AnyMergeStrategy::supports_ttl) can be wrapped.
+#[derive(Debug, Clone)]
+struct ExpiringMerger {
+    ttl: Duration,
+    strategy: AnyMergeStrategy,
+    values: VecDeque<(Instant, Value)>,
+}
+
+impl ExpiringMerger {
+    fn new(v: Value, strategy: AnyMergeStrategy, ttl: Duration) -> Self {
+        Self {
+            ttl,
+            strategy,
+            values: VecDeque::from([(Instant::now(), v)]),
+        }
+    }
+
+    fn evict_expired(&mut self) {
+        while let Some((received_at, _)) = self.values.front() {
+            if received_at.elapsed() < self.ttl {
+                break;
+            }
+            self.values.pop_front();
+        }
+    }
+}
+
+impl ReduceValueMerger for ExpiringMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        self.evict_expired();
+        if matches!(
+            self.strategy,
+            AnyMergeStrategy::Named(MergeStrategy::Retain)
+        ) {
+            // `retain` only ever keeps the latest non-null value, so nothing older is buffered
+            if v == Value::Null {
+                return Ok(());
+            }
+            self.values.clear();
+        }
+        self.values.push_back((Instant::now(), v));
+        Ok(())
+    }
+
+    fn insert_into(mut self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        self.evict_expired();
+
+        let Self {
+            strategy, values, ..
+        } = *self;
+        let mut values = values.into_iter().map(|(_, value)| value);
+        let mut merger = match values.next() {
+            Some(first) => get_any_value_merger(first, &strategy)?,
+            // Every value has expired, so the field is omitted.
+            None => return Ok(()),
+        };
+        for value in values {
+            if let Err(error) = merger.add(value) {
+                warn!(message = "Failed to merge value.", %error);
+            }
+        }
+        merger.insert_into(k, v)
+    }
+}
+
 pub trait ReduceValueMerger: std::fmt::Debug + Send + Sync {
     fn add(&mut self, v: Value) -> Result<(), String>;
     fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String>;
 }
 
-impl From<Value> for Box<dyn ReduceValueMerger> {
-    fn from(v: Value) -> Self {
-        match v {
-            Value::Integer(i) => Box::new(AddNumbersMerger::new(i.into())),
-            Value::Float(f) => Box::new(AddNumbersMerger::new(f.into())),
-            Value::Timestamp(ts) => Box::new(TimestampWindowMerger::new(ts)),
-            Value::Object(_) => Box::new(DiscardMerger::new(v)),
-            Value::Null => Box::new(DiscardMerger::new(v)),
-            Value::Boolean(_) => Box::new(DiscardMerger::new(v)),
-            Value::Bytes(_) => Box::new(DiscardMerger::new(v)),
-            Value::Regex(_) => Box::new(DiscardMerger::new(v)),
-            Value::Array(_) => Box::new(DiscardMerger::new(v)),
-        }
+/// Creates a merger for `v` with the default behavior, where the last value of a timestamp field
+/// is written to the field name followed by `timestamp_end_suffix`.
+pub(crate) fn get_default_value_merger(
+    v: Value,
+    timestamp_end_suffix: &str,
+) -> Box<dyn ReduceValueMerger> {
+    match v {
+        Value::Integer(i) => Box::new(AddNumbersMerger::new(i.into())),
+        Value::Float(f) => Box::new(AddNumbersMerger::new(f.into())),
+        Value::Timestamp(ts) => Box::new(TimestampWindowMerger::new(ts, timestamp_end_suffix)),
+        Value::Object(_) => Box::new(DiscardMerger::new(v)),
+        Value::Null => Box::new(DiscardMerger::new(v)),
+        Value::Boolean(_) => Box::new(DiscardMerger::new(v)),
+        Value::Bytes(_) => Box::new(DiscardMerger::new(v)),
+        Value::Regex(_) => Box::new(DiscardMerger::new(v)),
+        Value::Array(_) => Box::new(DiscardMerger::new(v)),
+    }
+}
+
+/// Creates a merger for `v` with a strategy given either by name or with options.
+pub(crate) fn get_any_value_merger(
+    v: Value,
+    m: &AnyMergeStrategy,
+) -> Result<Box<dyn ReduceValueMerger>, String> {
+    match m {
+        AnyMergeStrategy::Named(m) => get_value_merger(v, m),
+        AnyMergeStrategy::Options(m) => get_options_value_merger(v, m),
+    }
+}
+
+fn get_options_value_merger(
+    v: Value,
+    m: &MergeStrategyOptions,
+) -> Result<Box<dyn ReduceValueMerger>, String> {
+    match m {
+        MergeStrategyOptions::Concat { separator } => match v {
+            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, Some(separator.as_str())))),
+            Value::Array(a) => Ok(Box::new(ConcatArrayMerger::new(a))),
+            _ => Err(format!(
+                "expected string or array value, found: '{}'",
+                v.to_string_lossy()
+            )),
+        },
+        MergeStrategyOptions::ArrayMergeByKey { key } => match v {
+            Value::Array(a) => Ok(Box::new(ArrayMergeByKeyMerger::new(key.clone(), a))),
+            _ => Err(format!(
+                "expected array value, found: '{}'",
+                v.to_string_lossy()
+            )),
+        },
     }
 }
 
@@ -564,8 +1103,28 @@ pub(crate) fn get_value_merger(
                 v.to_string_lossy()
             )),
         },
+        MergeStrategy::Average => match v {
+            Value::Integer(i) => Ok(Box::new(AverageNumberMerger::new(i as f64))),
+            Value::Float(f) => Ok(Box::new(AverageNumberMerger::new(f.into_inner()))),
+            _ => Err(format!(
+                "expected number value, found: '{}'",
+                v.to_string_lossy()
+            )),
+        },
+        MergeStrategy::Count => Ok(Box::new(CountMerger::new())),
+        MergeStrategy::Variance | MergeStrategy::StdDev => {
+            let std_dev = matches!(m, MergeStrategy::StdDev);
+            match v {
+                Value::Integer(i) => Ok(Box::new(VarianceMerger::new(i as f64, std_dev))),
+                Value::Float(f) => Ok(Box::new(VarianceMerger::new(f.into_inner(), std_dev))),
+                _ => Err(format!(
+                    "expected number value, found: '{}'",
+                    v.to_string_lossy()
+                )),
+            }
+        }
         MergeStrategy::Concat => match v {
-            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, Some(' ')))),
+            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, Some(" ")))),
             Value::Array(a) => Ok(Box::new(ConcatArrayMerger::new(a))),
             _ => Err(format!(
                 "expected string or array value, found: '{}'",
@@ -573,7 +1132,7 @@ pub(crate) fn get_value_merger(
             )),
         },
         MergeStrategy::ConcatNewline => match v {
-            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, Some('\n')))),
+            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, Some("\n")))),
             _ => Err(format!(
                 "expected string value, found: '{}'",
                 v.to_string_lossy()
@@ -587,6 +1146,7 @@ pub(crate) fn get_value_merger(
             )),
         },
         MergeStrategy::Array => Ok(Box::new(ArrayMerger::new(v))),
+        MergeStrategy::Set => Ok(Box::new(SetMerger::new(v))),
         MergeStrategy::ShortestArray => match v {
             Value::Array(a) => Ok(Box::new(ShortestArrayMerger::new(a))),
             _ => Err(format!(
@@ -602,11 +1162,30 @@ pub(crate) fn get_value_merger(
             )),
         },
         MergeStrategy::Discard => Ok(Box::new(DiscardMerger::new(v))),
+        MergeStrategy::FirstLast => Ok(Box::new(FirstLastMerger::new(v))),
         MergeStrategy::Retain => Ok(Box::new(RetainMerger::new(v))),
         MergeStrategy::FlatUnique => Ok(Box::new(FlatUniqueMerger::new(v))),
     }
 }
 
+/// Creates a merger for `v` that only retains values received within `ttl`, merging them with the
+/// given strategy at flush time.
+pub(crate) fn get_expiring_value_merger(
+    v: Value,
+    m: &AnyMergeStrategy,
+    ttl: Duration,
+) -> Result<Box<dyn ReduceValueMerger>, String> {
+    if !m.supports_ttl() {
+        return Err(format!(
+            "the `{}` merge strategy does not support a TTL",
+            m.as_str()
+        ));
+    }
+    // Reject values the strategy can't handle up front, just like an unwrapped merger would.
+    get_any_value_merger(v.clone(), m)?;
+    Ok(Box::new(ExpiringMerger::new(v, m.clone(), ttl)))
+}
+
 #[cfg(test)]
 mod test {
     use serde_json::json;
@@ -616,11 +1195,14 @@ mod test {
 
     #[test]
     fn initial_values() {
+        let by_key = MergeStrategyOptions::ArrayMergeByKey { key: "id".into() };
+
         assert!(get_value_merger("foo".into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger("foo".into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger("foo".into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger("foo".into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger("foo".into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger("foo".into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger("foo".into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger("foo".into(), &MergeStrategy::LongestArray).is_err());
         assert!(get_value_merger("foo".into(), &MergeStrategy::ShortestArray).is_err());
@@ -628,11 +1210,13 @@ mod test {
         assert!(get_value_merger("foo".into(), &MergeStrategy::ConcatNewline).is_ok());
         assert!(get_value_merger("foo".into(), &MergeStrategy::ConcatRaw).is_ok());
         assert!(get_value_merger("foo".into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger("foo".into(), &by_key).is_err());
 
         assert!(get_value_merger(42.into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Sum).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Min).is_ok());
+        assert!(get_value_merger(42.into(), &MergeStrategy::Average).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Max).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::LongestArray).is_err());
@@ -641,11 +1225,13 @@ mod test {
         assert!(get_value_merger(42.into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(42.into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(42.into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(42.into(), &by_key).is_err());
 
         assert!(get_value_merger(42.into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(42.into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::Sum).is_ok());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::Min).is_ok());
+        assert!(get_value_merger(4.2.into(), &MergeStrategy::Average).is_ok());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::Max).is_ok());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::LongestArray).is_err());
@@ -654,12 +1240,14 @@ mod test {
         assert!(get_value_merger(4.2.into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(4.2.into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(4.2.into(), &by_key).is_err());
 
         assert!(get_value_merger(true.into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(true.into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(true.into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger(true.into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(true.into(), &MergeStrategy::LongestArray).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::ShortestArray).is_err());
@@ -667,12 +1255,14 @@ mod test {
         assert!(get_value_merger(true.into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(true.into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(true.into(), &by_key).is_err());
 
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::LongestArray).is_err());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::ShortestArray).is_err());
@@ -681,12 +1271,14 @@ mod test {
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(Utc::now().into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(Utc::now().into(), &by_key).is_err());
 
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger(json!([]).into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::LongestArray).is_ok());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::ShortestArray).is_ok());
@@ -694,12 +1286,14 @@ mod test {
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(json!([]).into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(json!([]).into(), &by_key).is_ok());
 
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger(json!({}).into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::LongestArray).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::ShortestArray).is_err());
@@ -707,12 +1301,14 @@ mod test {
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(json!({}).into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(json!({}).into(), &by_key).is_err());
 
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Discard).is_ok());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Retain).is_ok());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Sum).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Max).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Min).is_err());
+        assert!(get_value_merger(json!(null).into(), &MergeStrategy::Average).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Array).is_ok());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::LongestArray).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::ShortestArray).is_err());
@@ -720,6 +1316,7 @@ mod test {
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::ConcatRaw).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::FlatUnique).is_ok());
+        assert!(get_options_value_merger(json!(null).into(), &by_key).is_err());
     }
 
     #[test]
@@ -748,6 +1345,16 @@ mod test {
             merge("foo".into(), "bar".into(), &MergeStrategy::ConcatRaw),
             Ok("foobar".into())
         );
+        assert_eq!(
+            merge_with_options(
+                "foo".into(),
+                "bar".into(),
+                &MergeStrategyOptions::Concat {
+                    separator: ", ".into()
+                }
+            ),
+            Ok("foo, bar".into())
+        );
         assert!(merge("foo".into(), 42.into(), &MergeStrategy::Concat).is_err());
         assert!(merge("foo".into(), 4.2.into(), &MergeStrategy::Concat).is_err());
         assert!(merge("foo".into(), true.into(), &MergeStrategy::Concat).is_err());
@@ -782,6 +1389,23 @@ mod test {
             Ok(42.into())
         );
 
+        assert_eq!(
+            merge(2.into(), 3.into(), &MergeStrategy::Average),
+            Ok(2.5.into())
+        );
+        assert_eq!(
+            merge(2.into(), 4.into(), &MergeStrategy::Average),
+            Ok(3.0.into())
+        );
+        assert_eq!(
+            merge(1.into(), 2.0.into(), &MergeStrategy::Average),
+            Ok(1.5.into())
+        );
+        assert_eq!(
+            merge(1.5.into(), 2.into(), &MergeStrategy::Average),
+            Ok(1.75.into())
+        );
+
         assert_eq!(
             merge(2.1.into(), 2.1.into(), &MergeStrategy::Sum),
             Ok(4.2.into())
@@ -869,8 +1493,118 @@ mod test {
         }
     }
 
+    #[test]
+    fn expiring_values() {
+        let ttl = Duration::from_secs(60);
+        assert!(get_expiring_value_merger(42.into(), &MergeStrategy::Sum.into(), ttl).is_err());
+        assert!(get_expiring_value_merger(42.into(), &MergeStrategy::Array.into(), ttl).is_ok());
+
+        // `retain` only buffers the latest value
+        let mut merger = ExpiringMerger::new("a".into(), MergeStrategy::Retain.into(), ttl);
+        merger.add("b".into()).unwrap();
+        merger.add(Value::Null).unwrap();
+        assert_eq!(merger.values.len(), 1);
+
+        let mut output = LogEvent::default();
+        Box::new(merger)
+            .insert_into("out".into(), &mut output)
+            .unwrap();
+        assert_eq!(output["out"], "b".into());
+    }
+
+    #[test]
+    fn variance_and_std_dev() {
+        let values = [2, 4, 4, 4, 5, 5, 7, 9];
+        let reduce = |strategy: &MergeStrategy| {
+            let mut merger = get_value_merger(values[0].into(), strategy).unwrap();
+            for v in &values[1..] {
+                merger.add((*v).into()).unwrap();
+            }
+            let mut output = LogEvent::default();
+            merger.insert_into("out".into(), &mut output).unwrap();
+            output.remove("out").unwrap()
+        };
+
+        assert_eq!(reduce(&MergeStrategy::Variance), Value::from(4.0));
+        assert_eq!(reduce(&MergeStrategy::StdDev), Value::from(2.0));
+        assert_eq!(
+            merge(3.into(), 3.5.into(), &MergeStrategy::Variance),
+            Ok(0.0625.into())
+        );
+
+        let merger = get_value_merger(42.into(), &MergeStrategy::StdDev).unwrap();
+        let mut output = LogEvent::default();
+        merger.insert_into("out".into(), &mut output).unwrap();
+        assert_eq!(output["out"], Value::from(0.0));
+    }
+
+    #[test]
+    fn count() {
+        assert_eq!(
+            merge("foo".into(), "bar".into(), &MergeStrategy::Count),
+            Ok(2.into())
+        );
+        assert_eq!(
+            merge(42.into(), Value::Null, &MergeStrategy::Count),
+            Ok(2.into())
+        );
+    }
+
+    #[test]
+    fn set() {
+        let mut merger = get_value_merger("b".into(), &MergeStrategy::Set).unwrap();
+        for v in [
+            Value::from("a"),
+            Value::from("b"),
+            Value::from(1),
+            Value::from("a"),
+            Value::from(json!({ "x": 1 })),
+            Value::from(1),
+            Value::from(json!({ "x": 1 })),
+        ] {
+            merger.add(v).unwrap();
+        }
+        let mut output = LogEvent::default();
+        merger.insert_into("out".into(), &mut output).unwrap();
+        assert_eq!(
+            output["out"],
+            Value::from(vec![
+                Value::from("b"),
+                Value::from("a"),
+                Value::from(1),
+                Value::from(json!({ "x": 1 })),
+            ])
+        );
+    }
+
+    #[test]
+    fn first_last() {
+        let mut merger = get_value_merger("pending".into(), &MergeStrategy::FirstLast).unwrap();
+        merger.add("running".into()).unwrap();
+        merger.add("done".into()).unwrap();
+        let mut output = LogEvent::default();
+        merger.insert_into("out".into(), &mut output).unwrap();
+        assert_eq!(
+            output["out"],
+            json!({ "first": "pending", "last": "done" }).into()
+        );
+
+        assert_eq!(
+            merge(42.into(), 42.into(), &MergeStrategy::FirstLast),
+            Ok(json!({ "first": 42, "last": 42 }).into())
+        );
+    }
+
     fn merge(initial: Value, additional: Value, strategy: &MergeStrategy) -> Result<Value, String> {
-        let mut merger = get_value_merger(initial, strategy)?;
+        merge_with_options(initial, additional, &strategy.clone().into())
+    }
+
+    fn merge_with_options(
+        initial: Value,
+        additional: Value,
+        strategy: &AnyMergeStrategy,
+    ) -> Result<Value, String> {
+        let mut merger = get_any_value_merger(initial, strategy)?;
         merger.add(additional)?;
         let mut output = LogEvent::default();
         merger.insert_into("out".into(), &mut output)?;
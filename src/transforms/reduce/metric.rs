@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+use vector_config::configurable_component;
+use vector_core::config::log_schema;
+
+use super::{AnyMergeStrategy, MergeStrategy};
+use crate::{
+    event::{LogEvent, Metric, MetricKind, MetricTags, MetricValue, Value},
+    internal_events::ReduceUnsupportedMetric,
+};
+
+/// How metric events are handled by the transform.
+#[configurable_component]
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricHandling {
+    /// Metric events are not accepted.
+    #[derivative(Default)]
+    None,
+
+    /// Counter and gauge metrics are converted to log events before being reduced.
+    ///
+    /// Each metric becomes a log event with the fields `name`, `namespace`, `tags`, `kind`,
+    /// `type`, `value`, and `timestamp`, and combined events are emitted as logs.
+    ToLog,
+
+    /// Counter and gauge metrics are converted to log events before being reduced, and converted
+    /// back to metrics when the combined event is flushed.
+    ///
+    /// If a combined event can no longer be represented as a metric, it is emitted as a log.
+    RoundTrip,
+}
+
+impl MetricHandling {
+    /// Whether metric events are accepted as input.
+    pub const fn accepts_metrics(self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+/// Converts a counter or gauge metric into the flat log layout used for reducing.
+///
+/// Returns `None` for any other metric type.
+pub(super) fn metric_to_log(metric: Metric) -> Option<LogEvent> {
+    let (value_type, value) = match metric.value() {
+        MetricValue::Counter { value } => ("counter", *value),
+        MetricValue::Gauge { value } => ("gauge", *value),
+        other => {
+            emit!(ReduceUnsupportedMetric {
+                value_type: other.as_name(),
+            });
+            return None;
+        }
+    };
+    let kind = match metric.kind() {
+        MetricKind::Incremental => "incremental",
+        MetricKind::Absolute => "absolute",
+    };
+
+    let (series, data, metadata) = metric.into_parts();
+    let mut log = LogEvent::new_with_metadata(metadata);
+    log.insert("name", series.name.name);
+    if let Some(namespace) = series.name.namespace {
+        log.insert("namespace", namespace);
+    }
+    let tags = series
+        .tags
+        .map(|tags| {
+            tags.into_iter_single()
+                .map(|(k, v)| (k, Value::from(v)))
+                .collect::<BTreeMap<_, _>>()
+        })
+        .unwrap_or_default();
+    log.insert("tags", Value::Object(tags));
+    log.insert("kind", kind);
+    log.insert("type", value_type);
+    log.insert("value", Value::from_f64_or_zero(value));
+    if let Some(timestamp) = data.time.timestamp {
+        log.insert("timestamp", timestamp);
+    }
+
+    Some(log)
+}
+
+/// Converts a combined event produced from metrics back into a metric.
+///
+/// Returns the event unchanged if it no longer has the layout produced by [`metric_to_log`].
+pub(super) fn log_to_metric(log: LogEvent) -> Result<Metric, LogEvent> {
+    let get_str = |key: &str| {
+        log.get(key)
+            .and_then(Value::as_str)
+            .map(|value| value.into_owned())
+    };
+    let name = get_str("name");
+    let namespace = get_str("namespace");
+    let kind = match get_str("kind").as_deref() {
+        Some("incremental") => Some(MetricKind::Incremental),
+        Some("absolute") => Some(MetricKind::Absolute),
+        _ => None,
+    };
+    let value = match log.get("value") {
+        Some(Value::Float(value)) => Some(value.into_inner()),
+        Some(Value::Integer(value)) => Some(*value as f64),
+        _ => None,
+    };
+    let value = match (get_str("type").as_deref(), value) {
+        (Some("counter"), Some(value)) => Some(MetricValue::Counter { value }),
+        (Some("gauge"), Some(value)) => Some(MetricValue::Gauge { value }),
+        _ => None,
+    };
+    let tags = match log.get("tags") {
+        Some(Value::Object(tags)) => tags
+            .iter()
+            .map(|(k, v)| {
+                let value = String::from_utf8_lossy(&v.coerce_to_bytes()).into_owned();
+                (k.clone(), value)
+            })
+            .collect::<MetricTags>(),
+        _ => MetricTags::default(),
+    };
+    let timestamp = log.get("timestamp").and_then(Value::as_timestamp).copied();
+
+    match (name, kind, value) {
+        (Some(name), Some(kind), Some(value)) => {
+            let (_, metadata) = log.into_parts();
+            Ok(Metric::new_with_metadata(name, kind, value, metadata)
+                .with_namespace(namespace)
+                .with_tags(tags.as_option())
+                .with_timestamp(timestamp))
+        }
+        _ => Err(log),
+    }
+}
+
+/// Converts the numeric top-level fields of a combined event into metrics, tagged with the values
+/// of the `group_by` fields.
+///
+/// Returns the event unchanged if it has no numeric fields.
+pub(super) fn log_to_numeric_metrics(
+    log: LogEvent,
+    group_by: &[String],
+    merge_strategies: &IndexMap<String, AnyMergeStrategy>,
+) -> Result<Vec<Metric>, LogEvent> {
+    let tags = group_by
+        .iter()
+        .filter_map(|field| {
+            let value = log.get(field.as_str())?;
+            Some((field.clone(), value.to_string_lossy().into_owned()))
+        })
+        .collect::<MetricTags>();
+    let timestamp = log
+        .get(log_schema().timestamp_key())
+        .and_then(Value::as_timestamp)
+        .copied();
+
+    let metrics = log
+        .as_map()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| !group_by.contains(key))
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::Integer(value) => *value as f64,
+                Value::Float(value) => value.into_inner(),
+                _ => return None,
+            };
+            let (kind, value) = match merge_strategies.get(key) {
+                None | Some(AnyMergeStrategy::Named(MergeStrategy::Sum | MergeStrategy::Count)) => {
+                    (MetricKind::Incremental, MetricValue::Counter { value })
+                }
+                Some(_) => (MetricKind::Absolute, MetricValue::Gauge { value }),
+            };
+            Some((key.clone(), kind, value))
+        })
+        .collect::<Vec<_>>();
+    if metrics.is_empty() {
+        return Err(log);
+    }
+
+    let (_, metadata) = log.into_parts();
+    Ok(metrics
+        .into_iter()
+        .map(|(name, kind, value)| {
+            Metric::new_with_metadata(name, kind, value, metadata.clone())
+                .with_tags(tags.clone().as_option())
+                .with_timestamp(timestamp)
+        })
+        .collect())
+}
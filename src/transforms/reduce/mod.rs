@@ -1,34 +1,57 @@
 use std::collections::BTreeMap;
 use std::{
-    collections::{hash_map, HashMap},
+    collections::{hash_map, HashMap, HashSet},
     pin::Pin,
     time::{Duration, Instant},
 };
 
 use async_stream::stream;
-use futures::{stream, Stream, StreamExt};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use indexmap::IndexMap;
 use lookup::lookup_v2::parse_target_path;
-use lookup::PathPrefix;
+use lookup::{owned_value_path, PathPrefix};
 use serde_with::serde_as;
 use vector_config::configurable_component;
 
 use crate::{
     conditions::{AnyCondition, Condition},
     config::{DataType, Input, Output, TransformConfig, TransformContext},
-    event::{discriminant::Discriminant, Event, EventMetadata, LogEvent},
-    internal_events::ReduceStaleEventFlushed,
+    event::{
+        discriminant::Discriminant, Event, EventArray, EventContainer, EventMetadata, LogEvent,
+    },
+    internal_events::{
+        ReduceDuplicateDropped, ReduceGroupCardinalityExceeded, ReduceLoneEndDropped,
+        ReduceStaleEventFlushed, ReduceStateAge, ReduceStateFlushed, ReduceStatesActive,
+    },
     schema,
-    transforms::{TaskTransform, Transform},
+    transforms::{MultiOutputTaskTransform, TaskTransform, Transform, TransformOutputsBuf},
 };
 
+mod dedup;
 mod merge_strategy;
+mod metric;
+mod reorder;
+mod webhook;
 
 use crate::event::Value;
+use dedup::DedupFilter;
 pub use merge_strategy::*;
+pub use metric::MetricHandling;
+use metric::{log_to_metric, log_to_numeric_metrics, metric_to_log};
+use reorder::ReorderBuffer;
 use value::kind::Collection;
 use value::Kind;
-use vector_core::config::LogNamespace;
+use vector_core::{
+    config::{log_schema, LogNamespace},
+    ByteSizeOf,
+};
+use webhook::FlushWebhook;
+pub use webhook::FlushWebhookConfig;
+
+/// The output that events for groups beyond `max_group_cardinality` are sent to.
+const OVERFLOW_OUTPUT: &str = "overflow";
 
 /// Configuration for the `reduce` transform.
 #[serde_as]
@@ -58,6 +81,8 @@ pub struct ReduceConfig {
     ///
     /// For example, if `group_by = ["host", "region"]`, then all incoming events that have the same
     /// host and region will be grouped together before being reduced.
+    ///
+    /// Fields may be given as paths to group by nested values, such as `http.request_id`.
     #[serde(default)]
     #[configurable(metadata(
         docs::examples = "request_id",
@@ -73,12 +98,36 @@ pub struct ReduceConfig {
     ///
     /// The default behavior is as follows:
     ///
-    /// - The first value of a string field is kept, subsequent values are discarded.
+    /// - The first value of a string field is kept, subsequent values are discarded. This can be
+    ///   changed with `default_string_strategy`.
     /// - For timestamp fields the first is kept and a new field `[field-name]_end` is added with
-    ///   the last received timestamp value.
+    ///   the last received timestamp value. The `_end` suffix can be changed with
+    ///   `timestamp_end_suffix`.
     /// - Numeric values are summed.
+    ///
+    /// Strategies that take options are given as an object with the strategy name under `type`,
+    /// such as `{ type = "concat", separator = ", " }` or
+    /// `{ type = "array_merge_by_key", key = "id" }`.
+    #[serde(default)]
+    pub merge_strategies: IndexMap<String, AnyMergeStrategy>,
+
+    #[configurable(derived)]
+    pub conditional_merge_strategies: Option<ConditionalMergeStrategies>,
+
+    /// A map of field names to a time-to-live, in milliseconds, for the values merged into them.
+    ///
+    /// Values older than the TTL are dropped from the field while the transaction stays open, so
+    /// only values received within the TTL contribute to the combined event. This is useful for
+    /// keeping a rolling window of recent values, such as with the `array` strategy. If every value
+    /// of a field has expired, the field is omitted from the combined event.
+    ///
+    /// Each value is kept until it expires, so a TTL is only supported for fields merged with the
+    /// `array`, `concat`, `concat_newline`, `concat_raw` or `retain` strategies. A field without a
+    /// merge strategy can only have a TTL when `default_string_strategy` is `last`, in which case
+    /// its values must be strings.
     #[serde(default)]
-    pub merge_strategies: IndexMap<String, MergeStrategy>,
+    #[configurable(metadata(docs::additional_props_description = "The TTL for the field."))]
+    pub field_ttl_ms: IndexMap<String, u64>,
 
     /// A condition used to distinguish the final event of a transaction.
     ///
@@ -91,6 +140,249 @@ pub struct ReduceConfig {
     /// If this condition resolves to `true` for an event, the previous transaction is flushed
     /// (without this event) and a new transaction is started.
     pub starts_when: Option<AnyCondition>,
+
+    /// Whether to drop a final event that arrives when no transaction is open for its group.
+    ///
+    /// By default, an event matching `ends_when` with no previous events in its group is emitted
+    /// on its own as a single-event transaction. When enabled, such lone final events are dropped
+    /// instead.
+    #[serde(default)]
+    pub drop_lone_end: bool,
+
+    /// How metric events are handled.
+    ///
+    /// By default, only log events are accepted. Counter and gauge metrics can be reduced by
+    /// converting them to log events first, for example to sum counters grouped by `name` and
+    /// `tags`. Other metric types are dropped.
+    #[configurable(derived)]
+    #[serde(default)]
+    pub metric_handling: MetricHandling,
+
+    /// Whether to emit the numeric fields of combined events as metrics.
+    ///
+    /// When enabled, each numeric top-level field of a combined event is emitted as a metric named
+    /// after the field, tagged with the values of the `group_by` fields, instead of the combined
+    /// event itself. Fields combined with the `sum` or `count` strategies, including numeric fields
+    /// without a configured strategy, become incremental counters, and other numeric fields become
+    /// absolute gauges. Combined events without numeric fields are emitted as logs.
+    #[serde(default)]
+    pub emit_as_metric: bool,
+
+    /// The maximum number of events to combine into a single combined event.
+    ///
+    /// When a group has received this many events, its combined event is flushed immediately, and
+    /// subsequent events for the group start a new combined event.
+    ///
+    /// By default, there is no limit.
+    pub max_events: Option<usize>,
+
+    /// The maximum number of distinct groups the transform will track.
+    ///
+    /// Once this many distinct `group_by` values have been seen, events for any new group are not
+    /// reduced and are instead sent unchanged to the `overflow` output. Groups seen before the
+    /// limit was reached continue to be reduced, even after their transactions have been flushed.
+    ///
+    /// By default, there is no limit.
+    pub max_group_cardinality: Option<usize>,
+
+    /// The maximum number of expired combined events to flush every `flush_period_ms`.
+    ///
+    /// When set, combined events are only checked for expiry every `flush_period_ms` rather than
+    /// also after every event, and at most this many are flushed at a time, least recently
+    /// updated first. The rest are flushed on the following flush periods. This smooths the load
+    /// on downstream components when many groups expire at once. Combined events flushed because
+    /// of `ends_when`, `starts_when`, or `max_events` are still flushed immediately.
+    ///
+    /// By default, there is no limit.
+    pub max_flush_batch: Option<usize>,
+
+    /// Whether to record how each field of a combined event was merged.
+    ///
+    /// When enabled, every combined event gets a `reduce.audit` object with an entry per field. Each
+    /// entry holds the merge `strategy` applied to the field (`default` when none is configured) and
+    /// the number of values that contributed to it as `contributions`. This is intended for
+    /// debugging and is disabled by default.
+    #[serde(default)]
+    pub audit: bool,
+
+    /// Whether to record the values that contributed to each summed field.
+    ///
+    /// When enabled, every combined event gets a `reduce.provenance` object with an array per field
+    /// merged with the `sum` strategy, holding the values that were added together in the order
+    /// they were received. This allows a bad contribution to be corrected downstream. At most
+    /// `provenance_max_values` values are recorded per field.
+    #[serde(default)]
+    pub provenance: bool,
+
+    /// The maximum number of values to record per field when `provenance` is enabled.
+    ///
+    /// Values received after this limit is reached are still summed, but are not recorded.
+    #[serde(default = "default_provenance_max_values")]
+    #[derivative(Default(value = "default_provenance_max_values()"))]
+    pub provenance_max_values: usize,
+
+    /// Whether to describe the fields of each combined event.
+    ///
+    /// When enabled, every combined event gets a `reduce.schema` object with an entry per
+    /// top-level field holding the kind of its value, one of `string`, `integer`, `float`,
+    /// `boolean`, `timestamp`, `map`, `array`, or `null`. This helps downstream consumers parse
+    /// combined events whose fields vary. The `reduce` field itself is not described, and combined
+    /// events converted back to metrics are not given a description.
+    #[serde(default)]
+    pub schema: bool,
+
+    /// Whether to record why each combined event was flushed.
+    ///
+    /// When enabled, every combined event gets a `reduce.flush_reason` field holding the cause of
+    /// the flush: `expired` when no event arrived for the group within `expire_after_ms`,
+    /// `max_events` when the group reached `max_events`, `ends_when` or `starts_when` when an event
+    /// matched the respective condition, or `shutdown` when the transform stopped. This helps to
+    /// debug combined events that are flushed earlier than expected.
+    #[serde(default)]
+    pub annotate_flush_reason: bool,
+
+    /// Whether to emit the combined events flushed at the same time as a single event.
+    ///
+    /// When enabled, instead of emitting one event per group, the combined events flushed together
+    /// are emitted as one event holding them in its `events` array. This reduces per-event
+    /// overhead for destinations that ingest events in batches. Combined events converted back to
+    /// metrics are not batched.
+    #[serde(default)]
+    pub batch_output: bool,
+
+    #[configurable(derived)]
+    #[serde(default)]
+    pub primary_timestamp: PrimaryTimestamp,
+
+    #[configurable(derived)]
+    #[serde(default)]
+    pub default_string_strategy: DefaultStringStrategy,
+
+    /// The suffix of the field holding the last value of a timestamp field.
+    ///
+    /// Timestamp fields without a configured merge strategy keep their first value, and their last
+    /// value is written to a field named after the timestamp field followed by this suffix. Change
+    /// it when events already have fields ending with `_end` that would otherwise be overwritten.
+    /// The suffix must not be empty.
+    #[serde(default = "default_timestamp_end_suffix")]
+    #[derivative(Default(value = "default_timestamp_end_suffix()"))]
+    #[configurable(metadata(docs::examples = "_last"))]
+    pub timestamp_end_suffix: String,
+
+    /// The field used to detect duplicate events within a combined event.
+    ///
+    /// When set, an event whose value for this field has already been combined into the same
+    /// combined event is dropped. Events without this field are never considered duplicates.
+    pub dedup_field: Option<String>,
+
+    /// The size of the filter used to detect duplicate events, in bytes.
+    ///
+    /// Each combined event gets its own filter of this size. Seen values are tracked with a Bloom
+    /// filter, which uses bounded memory but can report false positives, where an event with a
+    /// value that has not been seen before is dropped as a duplicate. The filter is sized for one
+    /// distinct value per 10 bits, so the default of 1024 bytes holds about 800 values with a
+    /// false positive rate of about 1%. The rate grows as a combined event gets more distinct
+    /// values than that.
+    #[serde(default = "default_dedup_cache_size")]
+    #[derivative(Default(value = "default_dedup_cache_size()"))]
+    pub dedup_cache_size: usize,
+
+    /// The field to write an id for the group of a combined event to.
+    ///
+    /// The id is a hex digest of the `group_by` fields and values, so combined events for the same
+    /// group get the same id across flushes, restarts, instances, and releases. This is useful as a
+    /// compact key for joining with other systems. If unset, no id is written.
+    pub group_id_field: Option<String>,
+
+    /// Whether to write the `group_by` values of each combined event to a `_group` object.
+    ///
+    /// When enabled, every combined event gets a `_group` object holding the value of each
+    /// `group_by` field that was present on the events of its group, at the same path as in the
+    /// events. This identifies the group of a combined event even when a merge strategy changed or
+    /// discarded the `group_by` fields themselves.
+    #[serde(default)]
+    pub emit_group_key: bool,
+
+    #[configurable(derived)]
+    pub flush_webhook: Option<FlushWebhookConfig>,
+
+    /// An ordered list of fields by which to roll up combined events.
+    ///
+    /// When specified, every flushed combined event is also added to a rollup for its values of
+    /// these fields, which sums the numeric top-level fields of all combined events in the rollup.
+    /// Rollups are emitted alongside the combined events every `flush_period_ms`, and hold the
+    /// rollup fields and the summed numeric fields.
+    ///
+    /// For example, with `group_by = ["request_id"]` and `rollup_by = ["service"]`, an event is
+    /// emitted per request, and periodically an event per service with the totals of its requests.
+    #[serde(default)]
+    #[configurable(metadata(docs::examples = "service"))]
+    pub rollup_by: Vec<String>,
+
+    /// The timestamp field used to combine the events of a group in event-time order.
+    ///
+    /// When set, events are held back until they are more than `reorder_tolerance_ms` behind the
+    /// latest timestamp seen for their group, and are combined in timestamp order. This keeps
+    /// merge strategies that depend on order, such as `retain` or the `[field-name]_end`
+    /// timestamp, correct when events arrive slightly out of order. Held back events are also
+    /// combined once no event has arrived for their group for `reorder_tolerance_ms`, and events
+    /// that match `starts_when` or `ends_when` or have no timestamp in this field are never held
+    /// back.
+    #[configurable(metadata(docs::examples = "timestamp"))]
+    pub reorder_field: Option<String>,
+
+    /// How far out of order events may arrive within a group, in milliseconds.
+    ///
+    /// Only used when `reorder_field` is set.
+    #[serde(default = "default_reorder_tolerance_ms")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    #[derivative(Default(value = "default_reorder_tolerance_ms()"))]
+    pub reorder_tolerance_ms: Duration,
+
+    /// The field used to tag the state age metrics with a coarse group.
+    ///
+    /// On every flush, the `reduce_state_max_idle_seconds` and `reduce_oldest_state_age_seconds`
+    /// gauges report how long the open combined events have gone without a new event, and how
+    /// long the oldest of them has been open. When set, these gauges are reported separately for
+    /// each value of this field, taken from the first event of each combined event, in a `group`
+    /// tag. This field should have few distinct values, such as a service name, to keep the
+    /// number of reported series small.
+    #[configurable(metadata(docs::examples = "service"))]
+    pub state_age_group_field: Option<String>,
+}
+
+/// How string fields without a configured merge strategy are combined.
+#[configurable_component]
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultStringStrategy {
+    /// The first value of the field is kept, subsequent values are discarded.
+    #[derivative(Default)]
+    First,
+
+    /// The last value of the field is kept, previous values are discarded.
+    Last,
+}
+
+/// Which timestamp becomes the primary timestamp of a combined event.
+#[configurable_component]
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimaryTimestamp {
+    /// The timestamp of the first event is kept.
+    #[derivative(Default)]
+    First,
+
+    /// The timestamp of the last event is used.
+    ///
+    /// This only applies when the timestamp field is combined with the default behavior, which
+    /// also records the last timestamp in the `[field-name]_end` field.
+    Last,
+
+    /// The time at which the combined event is flushed is used.
+    FlushTime,
 }
 
 const fn default_expire_after_ms() -> Duration {
@@ -101,22 +393,64 @@ const fn default_flush_period_ms() -> Duration {
     Duration::from_millis(1000)
 }
 
+const fn default_dedup_cache_size() -> usize {
+    1024 // 1KiB
+}
+
+const fn default_provenance_max_values() -> usize {
+    100
+}
+
+const fn default_reorder_tolerance_ms() -> Duration {
+    Duration::from_millis(1000)
+}
+
+fn default_timestamp_end_suffix() -> String {
+    "_end".to_owned()
+}
+
 impl_generate_config_from_default!(ReduceConfig);
 
 #[async_trait::async_trait]
 impl TransformConfig for ReduceConfig {
     async fn build(&self, context: &TransformContext) -> crate::Result<Transform> {
-        Reduce::new(self, &context.enrichment_tables).map(Transform::event_task)
+        let mut reduce = Reduce::new(self, &context.enrichment_tables)?;
+        if let Some(flush_webhook) = &self.flush_webhook {
+            reduce.flush_webhook = Some(FlushWebhook::new(flush_webhook, &context.globals.proxy)?);
+        }
+        Ok(Transform::multi_output_task(reduce))
     }
 
     fn input(&self) -> Input {
-        Input::log()
+        if self.metric_handling.accepts_metrics() {
+            Input::new(DataType::Log | DataType::Metric)
+        } else {
+            Input::log()
+        }
     }
 
     fn outputs(&self, input: &schema::Definition, _: LogNamespace) -> Vec<Output> {
         let mut schema_definition = input.clone();
 
-        for (key, merge_strategy) in self.merge_strategies.iter() {
+        // a field can be merged with any of the strategies given for it, and fields with
+        // conditional strategies fall back to the default behavior for other events
+        let mut field_strategies = IndexMap::<&str, Vec<Option<&AnyMergeStrategy>>>::new();
+        for (key, merge_strategy) in &self.merge_strategies {
+            field_strategies
+                .entry(key.as_str())
+                .or_default()
+                .push(Some(merge_strategy));
+        }
+        if let Some(conditional) = &self.conditional_merge_strategies {
+            for (key, merge_strategy) in conditional.values.values().flatten() {
+                field_strategies
+                    .entry(key.as_str())
+                    .or_insert_with(|| vec![None])
+                    .push(Some(merge_strategy));
+            }
+        }
+
+        for (key, merge_strategies) in field_strategies {
             let key = if let Ok(key) = parse_target_path(key) {
                 key
             } else {
@@ -128,64 +462,12 @@ impl TransformConfig for ReduceConfig {
                 PathPrefix::Metadata => schema_definition.metadata_kind().at_path(&key.path),
             };
 
-            let new_kind = match merge_strategy {
-                MergeStrategy::Discard | MergeStrategy::Retain => {
-                    /* does not change the type */
-                    input_kind.clone()
-                }
-                MergeStrategy::Sum | MergeStrategy::Max | MergeStrategy::Min => {
-                    // only keeps integer / float values
-                    match (input_kind.contains_integer(), input_kind.contains_float()) {
-                        (true, true) => Kind::float().or_integer(),
-                        (true, false) => Kind::integer(),
-                        (false, true) => Kind::float(),
-                        (false, false) => Kind::undefined(),
-                    }
-                }
-                MergeStrategy::Array => {
-                    let unknown_kind = input_kind.clone();
-                    Kind::array(Collection::empty().with_unknown(unknown_kind))
-                }
-                MergeStrategy::Concat => {
-                    let mut new_kind = Kind::never();
-
-                    if input_kind.contains_bytes() {
-                        new_kind.add_bytes();
-                    }
-                    if let Some(array) = input_kind.as_array() {
-                        // array elements can be either any type that the field can be, or any
-                        // element of the array
-                        let array_elements = array.reduced_kind().union(input_kind.without_array());
-                        new_kind.add_array(Collection::empty().with_unknown(array_elements));
-                    }
-                    new_kind
-                }
-                MergeStrategy::ConcatNewline | MergeStrategy::ConcatRaw => {
-                    // can only produce bytes (or undefined)
-                    if input_kind.contains_bytes() {
-                        Kind::bytes()
-                    } else {
-                        Kind::undefined()
-                    }
-                }
-                MergeStrategy::ShortestArray | MergeStrategy::LongestArray => {
-                    if let Some(array) = input_kind.as_array() {
-                        Kind::array(array.clone())
-                    } else {
-                        Kind::undefined()
-                    }
-                }
-                MergeStrategy::FlatUnique => {
-                    let mut array_elements = input_kind.without_array().without_object();
-                    if let Some(array) = input_kind.as_array() {
-                        array_elements = array_elements.union(array.reduced_kind());
-                    }
-                    if let Some(object) = input_kind.as_object() {
-                        array_elements = array_elements.union(object.reduced_kind());
-                    }
-                    Kind::array(Collection::empty().with_unknown(array_elements))
-                }
-            };
+            let new_kind = merge_strategies
+                .iter()
+                .fold(Kind::never(), |kind, merge_strategy| match merge_strategy {
+                    Some(merge_strategy) => kind.union(merged_kind(merge_strategy, &input_kind)),
+                    None => kind.union(input_kind.clone()),
+                });
 
             // all of the merge strategies are optional. They won't produce a value unless a value actually exists
             let new_kind = if input_kind.contains_undefined() {
@@ -197,35 +479,425 @@ impl TransformConfig for ReduceConfig {
             schema_definition = schema_definition.with_field(&key, new_kind, None);
         }
 
-        vec![Output::default(DataType::Log).with_schema_definition(schema_definition)]
+        for key in self.field_ttl_ms.keys() {
+            let key = if let Ok(key) = parse_target_path(key) {
+                key
+            } else {
+                continue;
+            };
+
+            // every value of the field can expire, in which case it is omitted
+            let kind = match key.prefix {
+                PathPrefix::Event => schema_definition.event_kind().at_path(&key.path),
+                PathPrefix::Metadata => schema_definition.metadata_kind().at_path(&key.path),
+            }
+            .or_undefined();
+
+            schema_definition = schema_definition.with_field(&key, kind, None);
+        }
+
+        if self.audit {
+            let audit_kind = Kind::object(BTreeMap::from([
+                ("strategy".into(), Kind::bytes()),
+                ("contributions".into(), Kind::integer()),
+            ]));
+            schema_definition = schema_definition.with_event_field(
+                &owned_value_path!("reduce", "audit"),
+                Kind::object(Collection::empty().with_unknown(audit_kind)),
+                None,
+            );
+        }
+
+        if let Some(key) = self
+            .group_id_field
+            .as_ref()
+            .and_then(|field| parse_target_path(field).ok())
+        {
+            schema_definition = schema_definition.with_field(&key, Kind::bytes(), None);
+        }
+
+        if self.emit_group_key {
+            schema_definition = schema_definition.with_event_field(
+                &owned_value_path!("_group"),
+                Kind::any_object(),
+                None,
+            );
+        }
+
+        if self.annotate_flush_reason {
+            schema_definition = schema_definition.with_event_field(
+                &owned_value_path!("reduce", "flush_reason"),
+                Kind::bytes(),
+                None,
+            );
+        }
+
+        if self.schema {
+            schema_definition = schema_definition.with_event_field(
+                &owned_value_path!("reduce", "schema"),
+                Kind::object(Collection::empty().with_unknown(Kind::bytes())),
+                None,
+            );
+        }
+
+        if self.batch_output {
+            let batch_kind = Kind::object(BTreeMap::from([(
+                "events".into(),
+                Kind::array(
+                    Collection::empty().with_unknown(schema_definition.event_kind().clone()),
+                ),
+            )]));
+            schema_definition = schema::Definition::new(
+                batch_kind,
+                schema_definition.metadata_kind().clone(),
+                schema_definition.log_namespaces().clone(),
+            );
+        }
+
+        if !self.rollup_by.is_empty() {
+            // rollups only hold the rollup fields and numeric fields of the combined events
+            schema_definition = schema_definition.merge(schema::Definition::new(
+                Kind::any_object(),
+                schema_definition.metadata_kind().clone(),
+                schema_definition.log_namespaces().clone(),
+            ));
+        }
+
+        let data_type = match self.metric_handling {
+            _ if self.emit_as_metric => DataType::Log | DataType::Metric,
+            MetricHandling::RoundTrip => DataType::Log | DataType::Metric,
+            MetricHandling::None | MetricHandling::ToLog => DataType::Log,
+        };
+
+        let default_output = Output::default(data_type).with_schema_definition(schema_definition);
+
+        if self.max_group_cardinality.is_some() {
+            // overflow events are sent unchanged, so they keep the input schema
+            let overflow_data_type = match self.metric_handling {
+                MetricHandling::RoundTrip => DataType::Log | DataType::Metric,
+                MetricHandling::None | MetricHandling::ToLog => DataType::Log,
+            };
+            vec![
+                default_output,
+                Output::default(overflow_data_type)
+                    .with_schema_definition(input.clone())
+                    .with_port(OVERFLOW_OUTPUT),
+            ]
+        } else {
+            vec![default_output]
+        }
+    }
+}
+
+/// The kind of a field merged with the given strategy.
+fn merged_kind(merge_strategy: &AnyMergeStrategy, input_kind: &Kind) -> Kind {
+    match merge_strategy {
+        AnyMergeStrategy::Named(merge_strategy) => named_merged_kind(merge_strategy, input_kind),
+        AnyMergeStrategy::Options(MergeStrategyOptions::Concat { .. }) => {
+            // the separator doesn't change the kind of the concatenated values
+            named_merged_kind(&MergeStrategy::Concat, input_kind)
+        }
+        AnyMergeStrategy::Options(MergeStrategyOptions::ArrayMergeByKey { .. }) => {
+            // merged elements are objects, anything else is appended as-is
+            let mut array_elements = Kind::never();
+            if let Some(array) = input_kind.as_array() {
+                array_elements = array.reduced_kind();
+            }
+            if array_elements.is_never() {
+                Kind::undefined()
+            } else {
+                Kind::array(Collection::empty().with_unknown(array_elements))
+            }
+        }
+    }
+}
+
+fn named_merged_kind(merge_strategy: &MergeStrategy, input_kind: &Kind) -> Kind {
+    match merge_strategy {
+        MergeStrategy::Discard | MergeStrategy::Retain => {
+            /* does not change the type */
+            input_kind.clone()
+        }
+        MergeStrategy::Sum | MergeStrategy::Max | MergeStrategy::Min => {
+            // only keeps integer / float values
+            match (input_kind.contains_integer(), input_kind.contains_float()) {
+                (true, true) => Kind::float().or_integer(),
+                (true, false) => Kind::integer(),
+                (false, true) => Kind::float(),
+                (false, false) => Kind::undefined(),
+            }
+        }
+        MergeStrategy::Count => Kind::integer(),
+        MergeStrategy::FirstLast => Kind::object(BTreeMap::from([
+            ("first".into(), input_kind.clone()),
+            ("last".into(), input_kind.clone()),
+        ])),
+        MergeStrategy::Average | MergeStrategy::Variance | MergeStrategy::StdDev => {
+            // the mean or spread of any numeric values is always a float
+            if input_kind.contains_integer() || input_kind.contains_float() {
+                Kind::float()
+            } else {
+                Kind::undefined()
+            }
+        }
+        MergeStrategy::Array | MergeStrategy::Set => {
+            let unknown_kind = input_kind.clone();
+            Kind::array(Collection::empty().with_unknown(unknown_kind))
+        }
+        MergeStrategy::Concat => {
+            let mut new_kind = Kind::never();
+
+            if input_kind.contains_bytes() {
+                new_kind.add_bytes();
+            }
+            if let Some(array) = input_kind.as_array() {
+                // array elements can be either any type that the field can be, or any
+                // element of the array
+                let array_elements = array.reduced_kind().union(input_kind.without_array());
+                new_kind.add_array(Collection::empty().with_unknown(array_elements));
+            }
+            new_kind
+        }
+        MergeStrategy::ConcatNewline | MergeStrategy::ConcatRaw => {
+            // can only produce bytes (or undefined)
+            if input_kind.contains_bytes() {
+                Kind::bytes()
+            } else {
+                Kind::undefined()
+            }
+        }
+        MergeStrategy::ShortestArray | MergeStrategy::LongestArray => {
+            if let Some(array) = input_kind.as_array() {
+                Kind::array(array.clone())
+            } else {
+                Kind::undefined()
+            }
+        }
+        MergeStrategy::FlatUnique => {
+            let mut array_elements = input_kind.without_array().without_object();
+            if let Some(array) = input_kind.as_array() {
+                array_elements = array_elements.union(array.reduced_kind());
+            }
+            if let Some(object) = input_kind.as_object() {
+                array_elements = array_elements.union(object.reduced_kind());
+            }
+            Kind::array(Collection::empty().with_unknown(array_elements))
+        }
     }
 }
 
+/// How fields without a configured merge strategy are combined.
+#[derive(Clone, Debug)]
+struct MergeDefaults {
+    string_strategy: DefaultStringStrategy,
+    timestamp_end_suffix: String,
+}
+
 #[derive(Debug)]
 struct ReduceState {
     fields: HashMap<String, Box<dyn ReduceValueMerger>>,
+    created_at: Instant,
     stale_since: Instant,
+    age_group: Option<String>,
     metadata: EventMetadata,
+    events: usize,
+    bytes: usize,
+    into_metric: bool,
+    audit: Option<HashMap<String, FieldAudit>>,
+    provenance: Option<Provenance>,
+    dedup: Option<DedupFilter>,
+}
+
+#[derive(Debug)]
+struct FieldAudit {
+    strategy: &'static str,
+    contributions: usize,
+}
+
+fn record_contribution(
+    audit: &mut HashMap<String, FieldAudit>,
+    k: &str,
+    strategies: &IndexMap<String, AnyMergeStrategy>,
+) {
+    audit
+        .entry(k.to_string())
+        .or_insert_with(|| FieldAudit {
+            strategy: strategies
+                .get(k)
+                .map_or("default", AnyMergeStrategy::as_str),
+            contributions: 0,
+        })
+        .contributions += 1;
+}
+
+/// The values that contributed to the summed fields of a combined event.
+#[derive(Debug)]
+struct Provenance {
+    max_values: usize,
+    values: HashMap<String, Vec<Value>>,
+}
+
+impl Provenance {
+    fn new(max_values: usize) -> Self {
+        Self {
+            max_values,
+            values: HashMap::new(),
+        }
+    }
+
+    fn record(&mut self, k: &str, v: &Value, strategies: &IndexMap<String, AnyMergeStrategy>) {
+        if !matches!(
+            strategies.get(k),
+            Some(AnyMergeStrategy::Named(MergeStrategy::Sum))
+        ) {
+            return;
+        }
+        let values = self.values.entry(k.to_string()).or_default();
+        if values.len() < self.max_values {
+            values.push(v.clone());
+        }
+    }
+}
+
+/// The version of the encoding hashed into group ids. Changing the encoding changes the id of
+/// every group, so it must come with a new version.
+const GROUP_ID_VERSION: u8 = 1;
+
+/// Hashes the group-by fields and values into an id that is stable across restarts, instances,
+/// and releases.
+///
+/// The hashed bytes are an explicit encoding rather than the `Hash` implementation of the values,
+/// which is free to change.
+fn group_id_hash(group_by: &[String], discriminant: &Discriminant) -> String {
+    let mut encoded = vec![GROUP_ID_VERSION];
+    for (field, value) in group_by.iter().zip(discriminant.values()) {
+        encode_group_id_bytes(&mut encoded, field.as_bytes());
+        match value {
+            Some(value) => {
+                encoded.push(1);
+                encode_group_id_value(&mut encoded, value);
+            }
+            None => encoded.push(0),
+        }
+    }
+    format!("{:016x}", seahash::hash(&encoded))
+}
+
+fn encode_group_id_bytes(encoded: &mut Vec<u8>, bytes: &[u8]) {
+    encoded.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    encoded.extend_from_slice(bytes);
+}
+
+/// Encodes a value as a type tag followed by its contents. Numbers are little-endian, and
+/// variable-length contents are prefixed with their length.
+fn encode_group_id_value(encoded: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Bytes(bytes) => {
+            encoded.push(1);
+            encode_group_id_bytes(encoded, bytes);
+        }
+        Value::Regex(regex) => {
+            encoded.push(2);
+            encode_group_id_bytes(encoded, regex.as_bytes_slice());
+        }
+        Value::Integer(integer) => {
+            encoded.push(3);
+            encoded.extend_from_slice(&integer.to_le_bytes());
+        }
+        Value::Float(float) => {
+            encoded.push(4);
+            encoded.extend_from_slice(&float.into_inner().to_bits().to_le_bytes());
+        }
+        Value::Boolean(boolean) => {
+            encoded.push(5);
+            encoded.push(u8::from(*boolean));
+        }
+        Value::Timestamp(timestamp) => {
+            encoded.push(6);
+            encoded.extend_from_slice(&timestamp.timestamp().to_le_bytes());
+            encoded.extend_from_slice(&timestamp.timestamp_subsec_nanos().to_le_bytes());
+        }
+        Value::Object(object) => {
+            encoded.push(7);
+            encoded.extend_from_slice(&(object.len() as u64).to_le_bytes());
+            for (key, value) in object {
+                encode_group_id_bytes(encoded, key.as_bytes());
+                encode_group_id_value(encoded, value);
+            }
+        }
+        Value::Array(array) => {
+            encoded.push(8);
+            encoded.extend_from_slice(&(array.len() as u64).to_le_bytes());
+            for value in array {
+                encode_group_id_value(encoded, value);
+            }
+        }
+        Value::Null => encoded.push(9),
+    }
+}
+
+/// Builds an object holding the group-by values, at the same paths as in the grouped events.
+fn group_key(group_by: &[String], discriminant: &Discriminant) -> Value {
+    let mut group = LogEvent::default();
+    for (field, value) in group_by.iter().zip(discriminant.values()) {
+        if let Some(value) = value {
+            group.insert(field.as_str(), value.clone());
+        }
+    }
+    group.into_parts().0
+}
+
+fn get_field_merger(
+    k: &str,
+    v: Value,
+    strategies: &IndexMap<String, AnyMergeStrategy>,
+    field_ttls: &IndexMap<String, Duration>,
+    defaults: &MergeDefaults,
+) -> Result<Box<dyn ReduceValueMerger>, String> {
+    let retain = AnyMergeStrategy::Named(MergeStrategy::Retain);
+    let strategy = match (strategies.get(k), &v, defaults.string_strategy) {
+        (Some(strategy), _, _) => Some(strategy),
+        (None, Value::Bytes(_), DefaultStringStrategy::Last) => Some(&retain),
+        (None, _, _) => None,
+    };
+    match (field_ttls.get(k), strategy) {
+        (Some(ttl), Some(strategy)) => get_expiring_value_merger(v, strategy, *ttl),
+        (Some(_), None) => Err(format!(
+            "expected a string value for a field with a TTL and no merge strategy, found: '{}'",
+            v.to_string_lossy()
+        )),
+        (None, Some(strategy)) => get_any_value_merger(v, strategy),
+        (None, None) => Ok(get_default_value_merger(v, &defaults.timestamp_end_suffix)),
+    }
 }
 
 impl ReduceState {
-    fn new(e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) -> Self {
+    fn new(
+        e: LogEvent,
+        strategies: &IndexMap<String, AnyMergeStrategy>,
+        field_ttls: &IndexMap<String, Duration>,
+        defaults: &MergeDefaults,
+        into_metric: bool,
+        audit: bool,
+        provenance_max_values: Option<usize>,
+    ) -> Self {
+        let bytes = e.size_of();
         let (value, metadata) = e.into_parts();
+        let mut provenance = provenance_max_values.map(Provenance::new);
 
         let fields = if let Value::Object(fields) = value {
             fields
                 .into_iter()
                 .filter_map(|(k, v)| {
-                    if let Some(strat) = strategies.get(&k) {
-                        match get_value_merger(v, strat) {
-                            Ok(m) => Some((k, m)),
-                            Err(error) => {
-                                warn!(message = "Failed to create merger.", field = ?k, %error);
-                                None
-                            }
+                    if let Some(provenance) = provenance.as_mut() {
+                        provenance.record(&k, &v, strategies);
+                    }
+                    match get_field_merger(&k, v, strategies, field_ttls, defaults) {
+                        Ok(m) => Some((k, m)),
+                        Err(error) => {
+                            warn!(message = "Failed to create merger.", field = ?k, %error);
+                            None
                         }
-                    } else {
-                        Some((k, v.into()))
                     }
                 })
                 .collect()
@@ -233,14 +905,49 @@ impl ReduceState {
             HashMap::new()
         };
 
+        let audit = audit.then(|| {
+            let mut audit = HashMap::new();
+            for k in fields.keys() {
+                record_contribution(&mut audit, k, strategies);
+            }
+            audit
+        });
+
         Self {
+            created_at: Instant::now(),
             stale_since: Instant::now(),
+            age_group: None,
             fields,
             metadata,
+            events: 1,
+            bytes,
+            into_metric,
+            audit,
+            provenance,
+            dedup: None,
+        }
+    }
+
+    /// Records the dedup value of an event, returning whether it is a duplicate of one already
+    /// combined into this state.
+    fn is_duplicate(&mut self, dedup_value: Option<&Bytes>, cache_size: usize) -> bool {
+        match dedup_value {
+            Some(value) => self
+                .dedup
+                .get_or_insert_with(|| DedupFilter::new(cache_size))
+                .check_and_insert(value),
+            None => false,
         }
     }
 
-    fn add_event(&mut self, e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) {
+    fn add_event(
+        &mut self,
+        e: LogEvent,
+        strategies: &IndexMap<String, AnyMergeStrategy>,
+        field_ttls: &IndexMap<String, Duration>,
+        defaults: &MergeDefaults,
+    ) {
+        self.bytes += e.size_of();
         let (value, metadata) = e.into_parts();
         self.metadata.merge(metadata);
 
@@ -251,20 +958,22 @@ impl ReduceState {
         };
 
         for (k, v) in fields.into_iter() {
-            let strategy = strategies.get(&k);
+            if let Some(audit) = self.audit.as_mut() {
+                record_contribution(audit, &k, strategies);
+            }
+            if let Some(provenance) = self.provenance.as_mut() {
+                provenance.record(&k, &v, strategies);
+            }
+
             match self.fields.entry(k) {
                 hash_map::Entry::Vacant(entry) => {
-                    if let Some(strat) = strategy {
-                        match get_value_merger(v, strat) {
-                            Ok(m) => {
-                                entry.insert(m);
-                            }
-                            Err(error) => {
-                                warn!(message = "Failed to merge value.", %error);
-                            }
+                    match get_field_merger(entry.key(), v, strategies, field_ttls, defaults) {
+                        Ok(m) => {
+                            entry.insert(m);
+                        }
+                        Err(error) => {
+                            warn!(message = "Failed to merge value.", %error);
                         }
-                    } else {
-                        entry.insert(v.clone().into());
                     }
                 }
                 hash_map::Entry::Occupied(mut entry) => {
@@ -274,38 +983,245 @@ impl ReduceState {
                 }
             }
         }
+        self.events += 1;
         self.stale_since = Instant::now();
     }
 
-    fn flush(mut self) -> LogEvent {
+    fn flush(
+        mut self,
+        primary_timestamp: PrimaryTimestamp,
+        timestamp_end_suffix: &str,
+        group_id: Option<(&str, String)>,
+    ) -> Event {
         let mut event = LogEvent::new_with_metadata(self.metadata);
         for (k, v) in self.fields.drain() {
             if let Err(error) = v.insert_into(k, &mut event) {
                 warn!(message = "Failed to merge values for field.", %error);
             }
         }
-        event
-    }
-}
 
-pub struct Reduce {
-    expire_after: Duration,
-    flush_period: Duration,
-    group_by: Vec<String>,
-    merge_strategies: IndexMap<String, MergeStrategy>,
-    reduce_merge_states: HashMap<Discriminant, ReduceState>,
-    ends_when: Option<Condition>,
-    starts_when: Option<Condition>,
-}
+        let timestamp_key = log_schema().timestamp_key();
+        match primary_timestamp {
+            PrimaryTimestamp::First => {}
+            PrimaryTimestamp::Last => {
+                let end_key = format!("{}{}", timestamp_key, timestamp_end_suffix);
+                if let Some(Value::Timestamp(last)) = event.get(end_key.as_str()).cloned() {
+                    event.insert(timestamp_key, last);
+                }
+            }
+            PrimaryTimestamp::FlushTime => {
+                event.insert(timestamp_key, Utc::now());
+            }
+        }
 
-impl Reduce {
-    pub fn new(
-        config: &ReduceConfig,
+        if let Some((field, group_id)) = group_id {
+            event.insert(field, group_id);
+        }
+
+        if let Some(audit) = self.audit {
+            let audit = audit
+                .into_iter()
+                .map(|(k, field)| {
+                    let entry = BTreeMap::from([
+                        ("strategy".into(), field.strategy.into()),
+                        ("contributions".into(), field.contributions.into()),
+                    ]);
+                    (k, Value::Object(entry))
+                })
+                .collect::<BTreeMap<_, _>>();
+            event.insert("reduce.audit", Value::Object(audit));
+        }
+
+        if let Some(provenance) = self.provenance {
+            let provenance = provenance
+                .values
+                .into_iter()
+                .map(|(k, values)| (k, Value::Array(values)))
+                .collect::<BTreeMap<_, _>>();
+            event.insert("reduce.provenance", Value::Object(provenance));
+        }
+
+        if !self.into_metric {
+            return event.into();
+        }
+        match log_to_metric(event) {
+            Ok(metric) => metric.into(),
+            Err(event) => {
+                warn!(message = "Failed to convert combined event back to a metric.");
+                event.into()
+            }
+        }
+    }
+}
+
+/// Writes the kind of each top-level field of a combined event to `reduce.schema`.
+fn describe_fields(log: &mut LogEvent) {
+    let schema = log
+        .as_map()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| key.as_str() != "reduce")
+        .map(|(key, value)| (key.clone(), Value::from(value.kind_str())))
+        .collect::<BTreeMap<_, _>>();
+    log.insert("reduce.schema", Value::Object(schema));
+}
+
+/// The merge strategies of the transform, selected per event.
+struct MergeStrategies {
+    default: IndexMap<String, AnyMergeStrategy>,
+    field: Option<String>,
+    by_value: HashMap<String, IndexMap<String, AnyMergeStrategy>>,
+}
+
+impl MergeStrategies {
+    fn new(config: &ReduceConfig) -> Self {
+        let default = config.merge_strategies.clone();
+        let by_value = config
+            .conditional_merge_strategies
+            .iter()
+            .flat_map(|conditional| &conditional.values)
+            .map(|(value, strategies)| {
+                let mut strategies_for_value = default.clone();
+                for (k, strategy) in strategies {
+                    strategies_for_value.insert(k.clone(), strategy.clone());
+                }
+                (value.clone(), strategies_for_value)
+            })
+            .collect();
+
+        Self {
+            default,
+            field: config
+                .conditional_merge_strategies
+                .as_ref()
+                .map(|conditional| conditional.field.clone()),
+            by_value,
+        }
+    }
+
+    fn for_event(&self, event: &LogEvent) -> &IndexMap<String, AnyMergeStrategy> {
+        self.field
+            .as_deref()
+            .and_then(|field| event.get(field))
+            .and_then(|value| self.by_value.get(&*value.to_string_lossy()))
+            .unwrap_or(&self.default)
+    }
+}
+
+pub struct Reduce {
+    expire_after: Duration,
+    flush_period: Duration,
+    group_by: Vec<String>,
+    merge_strategies: MergeStrategies,
+    field_ttls: IndexMap<String, Duration>,
+    reduce_merge_states: HashMap<Discriminant, ReduceState>,
+    ends_when: Option<Condition>,
+    starts_when: Option<Condition>,
+    drop_lone_end: bool,
+    metric_handling: MetricHandling,
+    emit_as_metric: bool,
+    max_events: Option<usize>,
+    max_group_cardinality: Option<usize>,
+    max_flush_batch: Option<usize>,
+    seen_groups: HashSet<Discriminant>,
+    audit: bool,
+    provenance_max_values: Option<usize>,
+    schema: bool,
+    annotate_flush_reason: bool,
+    batch_output: bool,
+    primary_timestamp: PrimaryTimestamp,
+    merge_defaults: MergeDefaults,
+    dedup_field: Option<String>,
+    dedup_cache_size: usize,
+    group_id_field: Option<String>,
+    emit_group_key: bool,
+    flush_webhook: Option<FlushWebhook>,
+    rollup_by: Vec<String>,
+    rollup_strategies: IndexMap<String, AnyMergeStrategy>,
+    rollup_states: HashMap<Discriminant, ReduceState>,
+    reorder_field: Option<String>,
+    reorder_tolerance: Duration,
+    reorder_buffers: HashMap<Discriminant, ReorderBuffer>,
+    state_age_group_field: Option<String>,
+    state_age_groups: HashSet<String>,
+}
+
+impl Reduce {
+    pub fn new(
+        config: &ReduceConfig,
         enrichment_tables: &enrichment::TableRegistry,
     ) -> crate::Result<Self> {
         if config.ends_when.is_some() && config.starts_when.is_some() {
             return Err("only one of `ends_when` and `starts_when` can be provided".into());
         }
+        if config.timestamp_end_suffix.is_empty() {
+            return Err("`timestamp_end_suffix` must not be empty".into());
+        }
+        if config.max_flush_batch == Some(0) {
+            return Err("`max_flush_batch` must be greater than zero".into());
+        }
+        if config.dedup_cache_size == 0 {
+            return Err("`dedup_cache_size` must be greater than zero".into());
+        }
+
+        let conditional_keys = config
+            .conditional_merge_strategies
+            .iter()
+            .flat_map(|conditional| conditional.values.values().flat_map(IndexMap::keys));
+        let invalid_keys = config
+            .merge_strategies
+            .keys()
+            .chain(conditional_keys)
+            .filter(|key| parse_target_path(key).is_err())
+            .map(|key| format!("`{}`", key))
+            .collect::<Vec<_>>();
+        if !invalid_keys.is_empty() {
+            return Err(format!(
+                "invalid field paths in merge strategies: {}",
+                invalid_keys.join(", ")
+            )
+            .into());
+        }
+
+        for key in config.field_ttl_ms.keys() {
+            let conditional_strategies = config
+                .conditional_merge_strategies
+                .iter()
+                .flat_map(|conditional| conditional.values.values())
+                .filter_map(|strategies| strategies.get(key));
+            if let Some(strategy) = config
+                .merge_strategies
+                .get(key)
+                .into_iter()
+                .chain(conditional_strategies)
+                .find(|strategy| !strategy.supports_ttl())
+            {
+                let supported = TTL_MERGE_STRATEGIES
+                    .iter()
+                    .map(|strategy| format!("`{}`", strategy.as_str()))
+                    .collect::<Vec<_>>();
+                let (last, rest) = supported
+                    .split_last()
+                    .expect("TTL strategies are not empty");
+                return Err(format!(
+                    "`field_ttl_ms` is only supported for the {} and {} merge strategies, but `{}` uses `{}`",
+                    rest.join(", "),
+                    last,
+                    key,
+                    strategy.as_str()
+                )
+                .into());
+            }
+            if !config.merge_strategies.contains_key(key)
+                && config.default_string_strategy != DefaultStringStrategy::Last
+            {
+                return Err(format!(
+                    "`field_ttl_ms` needs a merge strategy for `{}`, unless `default_string_strategy` is `last`",
+                    key
+                )
+                .into());
+            }
+        }
 
         let ends_when = config
             .ends_when
@@ -317,52 +1233,385 @@ impl Reduce {
             .as_ref()
             .map(|c| c.build(enrichment_tables))
             .transpose()?;
-        let group_by = config.group_by.clone().into_iter().collect();
+        let mut group_by: Vec<String> = config.group_by.clone();
+        // Each combined event must be merged with the strategies of a single selector value.
+        if let Some(conditional) = &config.conditional_merge_strategies {
+            if !group_by.contains(&conditional.field) {
+                group_by.push(conditional.field.clone());
+            }
+        }
 
         Ok(Reduce {
             expire_after: config.expire_after_ms,
             flush_period: config.flush_period_ms,
             group_by,
-            merge_strategies: config.merge_strategies.clone(),
+            merge_strategies: MergeStrategies::new(config),
+            field_ttls: config
+                .field_ttl_ms
+                .iter()
+                .map(|(k, ttl)| (k.clone(), Duration::from_millis(*ttl)))
+                .collect(),
             reduce_merge_states: HashMap::new(),
             ends_when,
             starts_when,
+            drop_lone_end: config.drop_lone_end,
+            metric_handling: config.metric_handling,
+            emit_as_metric: config.emit_as_metric,
+            max_events: config.max_events,
+            max_group_cardinality: config.max_group_cardinality,
+            max_flush_batch: config.max_flush_batch,
+            seen_groups: HashSet::new(),
+            audit: config.audit,
+            provenance_max_values: config.provenance.then_some(config.provenance_max_values),
+            schema: config.schema,
+            annotate_flush_reason: config.annotate_flush_reason,
+            batch_output: config.batch_output,
+            primary_timestamp: config.primary_timestamp,
+            merge_defaults: MergeDefaults {
+                string_strategy: config.default_string_strategy,
+                timestamp_end_suffix: config.timestamp_end_suffix.clone(),
+            },
+            dedup_field: config.dedup_field.clone(),
+            dedup_cache_size: config.dedup_cache_size,
+            group_id_field: config.group_id_field.clone(),
+            emit_group_key: config.emit_group_key,
+            flush_webhook: None,
+            rollup_by: config.rollup_by.clone(),
+            // the rollup fields themselves are kept as-is rather than summed
+            rollup_strategies: config
+                .rollup_by
+                .iter()
+                .map(|field| (field.clone(), MergeStrategy::Discard.into()))
+                .collect(),
+            rollup_states: HashMap::new(),
+            reorder_field: config.reorder_field.clone(),
+            reorder_tolerance: config.reorder_tolerance_ms,
+            reorder_buffers: HashMap::new(),
+            state_age_group_field: config.state_age_group_field.clone(),
+            state_age_groups: HashSet::new(),
         })
     }
 
-    fn flush_into(&mut self, output: &mut Vec<Event>) {
+    /// Flushes the combined events that are due. Expired combined events are left for the
+    /// `periodic` flushes when `max_flush_batch` is set, which also report the open states.
+    fn flush_into(&mut self, output: &mut Vec<Event>, periodic: bool) {
+        let idle_buffers = self
+            .reorder_buffers
+            .iter()
+            .filter(|(_, buffer)| buffer.is_idle(self.reorder_tolerance))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for k in idle_buffers {
+            self.release_buffered(&k);
+        }
+
+        let flush_expired = periodic || self.max_flush_batch.is_none();
         let mut flush_discriminants = Vec::new();
+        let mut expired = Vec::new();
         for (k, t) in &self.reduce_merge_states {
-            if t.stale_since.elapsed() >= self.expire_after {
-                flush_discriminants.push(k.clone());
+            if self.max_events.map_or(false, |max| t.events >= max) {
+                flush_discriminants.push((k.clone(), "max_events"));
+            } else if flush_expired && t.stale_since.elapsed() >= self.expire_after {
+                expired.push((t.stale_since, k.clone()));
+            }
+        }
+        if let Some(max_flush_batch) = self.max_flush_batch {
+            expired.sort_by_key(|(stale_since, _)| *stale_since);
+            expired.truncate(max_flush_batch);
+        }
+        flush_discriminants.extend(expired.into_iter().map(|(_, k)| (k, "expired")));
+        for (k, reason) in flush_discriminants {
+            if let Some(t) = self.reduce_merge_states.remove(&k) {
+                if reason == "expired" {
+                    emit!(ReduceStaleEventFlushed);
+                }
+                emit!(ReduceStateFlushed { reason });
+                self.flush_state(t, &k, reason, output);
             }
         }
-        for k in &flush_discriminants {
-            if let Some(t) = self.reduce_merge_states.remove(k) {
-                emit!(ReduceStaleEventFlushed);
-                output.push(Event::from(t.flush()));
+
+        // The gauges only need to be as fresh as the flush period, and walking every state after
+        // each event would make the transform quadratic in the number of open states.
+        if periodic {
+            emit!(ReduceStatesActive {
+                count: self.reduce_merge_states.len(),
+                bytes: self.reduce_merge_states.values().map(|t| t.bytes).sum(),
+            });
+            self.emit_state_ages();
+        }
+    }
+
+    fn emit_state_ages(&mut self) {
+        let mut ages = HashMap::<Option<&str>, (Duration, Duration)>::new();
+        for state in self.reduce_merge_states.values() {
+            let (max_idle, oldest) = ages.entry(state.age_group.as_deref()).or_default();
+            *max_idle = (*max_idle).max(state.stale_since.elapsed());
+            *oldest = (*oldest).max(state.created_at.elapsed());
+        }
+
+        if self.state_age_group_field.is_none() {
+            let (max_idle, oldest) = ages.remove(&None).unwrap_or_default();
+            emit!(ReduceStateAge {
+                group: None,
+                max_idle,
+                oldest,
+            });
+            return;
+        }
+
+        // Groups without open states are reset, so their gauges don't keep reporting stale ages.
+        for group in &self.state_age_groups {
+            if !ages.contains_key(&Some(group.as_str())) {
+                emit!(ReduceStateAge {
+                    group: Some(group),
+                    max_idle: Duration::ZERO,
+                    oldest: Duration::ZERO,
+                });
             }
         }
+        let mut groups = HashSet::new();
+        for (group, (max_idle, oldest)) in ages {
+            emit!(ReduceStateAge {
+                group,
+                max_idle,
+                oldest,
+            });
+            groups.extend(group.map(str::to_owned));
+        }
+        self.state_age_groups = groups;
     }
 
     fn flush_all_into(&mut self, output: &mut Vec<Event>) {
-        self.reduce_merge_states
-            .drain()
-            .for_each(|(_, s)| output.push(Event::from(s.flush())));
+        let buffered = self.reorder_buffers.keys().cloned().collect::<Vec<_>>();
+        for k in buffered {
+            self.release_buffered(&k);
+        }
+
+        let states = std::mem::take(&mut self.reduce_merge_states);
+        for (discriminant, state) in states {
+            emit!(ReduceStateFlushed { reason: "shutdown" });
+            self.flush_state(state, &discriminant, "shutdown", output);
+        }
+        self.flush_rollups_into(output);
+    }
+
+    fn flush_rollups_into(&mut self, output: &mut Vec<Event>) {
+        self.rollup_states.drain().for_each(|(_, s)| {
+            output.push(s.flush(
+                PrimaryTimestamp::First,
+                &self.merge_defaults.timestamp_end_suffix,
+                None,
+            ))
+        });
+    }
+
+    fn add_to_rollup(&mut self, event: &Event) {
+        let log = match event.maybe_as_log() {
+            Some(log) if !self.rollup_by.is_empty() => log,
+            _ => return,
+        };
+
+        let numeric_fields = log
+            .as_map()
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| matches!(value, Value::Integer(_) | Value::Float(_)))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let mut rollup = LogEvent::from(numeric_fields);
+        for field in &self.rollup_by {
+            if let Some(value) = log.get(field.as_str()) {
+                rollup.insert(field.as_str(), value.clone());
+            }
+        }
+
+        let discriminant = Discriminant::from_log_event(&rollup, &self.rollup_by);
+        match self.rollup_states.entry(discriminant) {
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(ReduceState::new(
+                    rollup,
+                    &self.rollup_strategies,
+                    &IndexMap::new(),
+                    &self.merge_defaults,
+                    false,
+                    false,
+                    None,
+                ));
+            }
+            hash_map::Entry::Occupied(mut entry) => {
+                entry.get_mut().add_event(
+                    rollup,
+                    &self.rollup_strategies,
+                    &IndexMap::new(),
+                    &self.merge_defaults,
+                );
+            }
+        }
+    }
+
+    fn flush_state(
+        &mut self,
+        state: ReduceState,
+        discriminant: &Discriminant,
+        reason: &'static str,
+        output: &mut Vec<Event>,
+    ) {
+        let event_count = state.events;
+        let group_id = self
+            .group_id_field
+            .as_deref()
+            .map(|field| (field, group_id_hash(&self.group_by, discriminant)));
+        let mut event = state.flush(
+            self.primary_timestamp,
+            &self.merge_defaults.timestamp_end_suffix,
+            group_id,
+        );
+        if self.emit_group_key {
+            if let Event::Log(log) = &mut event {
+                log.insert("_group", group_key(&self.group_by, discriminant));
+            }
+        }
+        if self.annotate_flush_reason {
+            if let Event::Log(log) = &mut event {
+                log.insert("reduce.flush_reason", reason);
+            }
+        }
+        if self.schema {
+            if let Event::Log(log) = &mut event {
+                describe_fields(log);
+            }
+        }
+        if let Some(flush_webhook) = &self.flush_webhook {
+            flush_webhook.notify(&self.group_by, discriminant, event_count);
+        }
+        self.add_to_rollup(&event);
+        match event {
+            Event::Log(log) if self.emit_as_metric => {
+                let merge_strategies = self.merge_strategies.for_event(&log);
+                match log_to_numeric_metrics(log, &self.group_by, merge_strategies) {
+                    Ok(metrics) => output.extend(metrics.into_iter().map(Event::from)),
+                    Err(log) => output.push(log.into()),
+                }
+            }
+            event => output.push(event),
+        }
     }
 
-    fn push_or_new_reduce_state(&mut self, event: LogEvent, discriminant: Discriminant) {
+    fn push_or_new_reduce_state(
+        &mut self,
+        event: LogEvent,
+        discriminant: Discriminant,
+        into_metric: bool,
+        dedup_value: Option<&Bytes>,
+    ) {
+        let merge_strategies = self.merge_strategies.for_event(&event);
         match self.reduce_merge_states.entry(discriminant) {
             hash_map::Entry::Vacant(entry) => {
-                entry.insert(ReduceState::new(event, &self.merge_strategies));
+                let age_group = self.state_age_group_field.as_ref().map(|field| {
+                    event.get(field.as_str()).map_or_else(String::new, |value| {
+                        String::from_utf8_lossy(&value.coerce_to_bytes()).into_owned()
+                    })
+                });
+                // Record the first event's value so later duplicates of it are caught.
+                let state = entry.insert(ReduceState::new(
+                    event,
+                    merge_strategies,
+                    &self.field_ttls,
+                    &self.merge_defaults,
+                    into_metric,
+                    self.audit,
+                    self.provenance_max_values,
+                ));
+                state.age_group = age_group;
+                state.is_duplicate(dedup_value, self.dedup_cache_size);
             }
             hash_map::Entry::Occupied(mut entry) => {
-                entry.get_mut().add_event(event, &self.merge_strategies);
+                if entry
+                    .get_mut()
+                    .is_duplicate(dedup_value, self.dedup_cache_size)
+                {
+                    emit!(ReduceDuplicateDropped);
+                    return;
+                }
+                entry.get_mut().add_event(
+                    event,
+                    merge_strategies,
+                    &self.field_ttls,
+                    &self.merge_defaults,
+                );
+            }
+        }
+    }
+
+    /// Holds back an event for reordering, combining the events of its group that are now far
+    /// enough behind the latest event time.
+    fn buffer_event(
+        &mut self,
+        event: LogEvent,
+        discriminant: Discriminant,
+        time: DateTime<Utc>,
+        into_metric: bool,
+    ) {
+        let buffer = self
+            .reorder_buffers
+            .entry(discriminant.clone())
+            .or_insert_with(ReorderBuffer::new);
+        buffer.push(time, event, into_metric);
+        for (event, into_metric) in buffer.release(self.reorder_tolerance) {
+            self.reduce_event(event, discriminant.clone(), into_metric);
+        }
+    }
+
+    /// Combines all events held back for a group, in event-time order.
+    fn release_buffered(&mut self, discriminant: &Discriminant) {
+        if let Some(buffer) = self.reorder_buffers.remove(discriminant) {
+            for (event, into_metric) in buffer.into_sorted() {
+                self.reduce_event(event, discriminant.clone(), into_metric);
             }
         }
     }
 
-    fn transform_one(&mut self, output: &mut Vec<Event>, event: Event) {
+    fn dedup_value(&self, event: &LogEvent) -> Option<Bytes> {
+        self.dedup_field
+            .as_deref()
+            .and_then(|field| event.get(field))
+            .map(Value::coerce_to_bytes)
+    }
+
+    fn reduce_event(&mut self, event: LogEvent, discriminant: Discriminant, into_metric: bool) {
+        let dedup_value = self.dedup_value(&event);
+        self.push_or_new_reduce_state(event, discriminant, into_metric, dedup_value.as_ref())
+    }
+
+    /// Creates a buffer for every output of the transform.
+    fn new_outputs_buf(&self) -> TransformOutputsBuf {
+        let mut outputs = vec![Output::default(DataType::all())];
+        if self.max_group_cardinality.is_some() {
+            outputs.push(Output::default(DataType::all()).with_port(OVERFLOW_OUTPUT));
+        }
+        TransformOutputsBuf::new_with_capacity(outputs, 1)
+    }
+
+    /// Sends the combined events flushed together to the default output.
+    fn append_flushed(&self, output: &mut TransformOutputsBuf, mut flushed: Vec<Event>) {
+        if self.batch_output {
+            flushed = batch_flushed(flushed);
+        }
+        output.append(&mut flushed);
+    }
+
+    fn transform_one(&mut self, output: &mut TransformOutputsBuf, event: Event) {
+        let (event, into_metric) = match event {
+            Event::Metric(metric) => match metric_to_log(metric) {
+                Some(log) => (
+                    log.into(),
+                    self.metric_handling == MetricHandling::RoundTrip,
+                ),
+                None => return,
+            },
+            event => (event, false),
+        };
+
         let (starts_here, event) = match &self.starts_when {
             Some(condition) => condition.check(event),
             None => (false, event),
@@ -376,35 +1625,120 @@ impl Reduce {
         let event = event.into_log();
         let discriminant = Discriminant::from_log_event(&event, &self.group_by);
 
+        if let Some(max_group_cardinality) = self.max_group_cardinality {
+            if !self.seen_groups.contains(&discriminant) {
+                if self.seen_groups.len() >= max_group_cardinality {
+                    emit!(ReduceGroupCardinalityExceeded);
+                    let event = if into_metric {
+                        log_to_metric(event).map_or_else(Event::from, Event::from)
+                    } else {
+                        event.into()
+                    };
+                    output.push_named(OVERFLOW_OUTPUT, event);
+                    return;
+                }
+                self.seen_groups.insert(discriminant.clone());
+            }
+        }
+
+        if starts_here || ends_here {
+            self.release_buffered(&discriminant);
+        } else if let Some(time) = self
+            .reorder_field
+            .as_deref()
+            .and_then(|field| event.get(field))
+            .and_then(Value::as_timestamp)
+            .copied()
+        {
+            self.buffer_event(event, discriminant, time, into_metric);
+            let mut flushed = Vec::new();
+            self.flush_into(&mut flushed, false);
+            self.append_flushed(output, flushed);
+            return;
+        }
+
+        let mut flushed = Vec::new();
+
         if starts_here {
             if let Some(state) = self.reduce_merge_states.remove(&discriminant) {
-                output.push(state.flush().into());
+                self.flush_state(state, &discriminant, "starts_when", &mut flushed);
             }
 
-            self.push_or_new_reduce_state(event, discriminant)
+            self.reduce_event(event, discriminant, into_metric)
         } else if ends_here {
-            output.push(match self.reduce_merge_states.remove(&discriminant) {
+            match self.reduce_merge_states.remove(&discriminant) {
                 Some(mut state) => {
-                    state.add_event(event, &self.merge_strategies);
-                    state.flush().into()
+                    let dedup_value = self.dedup_value(&event);
+                    if state.is_duplicate(dedup_value.as_ref(), self.dedup_cache_size) {
+                        emit!(ReduceDuplicateDropped);
+                    } else {
+                        let merge_strategies = self.merge_strategies.for_event(&event);
+                        state.add_event(
+                            event,
+                            merge_strategies,
+                            &self.field_ttls,
+                            &self.merge_defaults,
+                        );
+                    }
+                    self.flush_state(state, &discriminant, "ends_when", &mut flushed);
                 }
-                None => ReduceState::new(event, &self.merge_strategies)
-                    .flush()
-                    .into(),
-            })
+                None if self.drop_lone_end => emit!(ReduceLoneEndDropped),
+                None => {
+                    let merge_strategies = self.merge_strategies.for_event(&event);
+                    let state = ReduceState::new(
+                        event,
+                        merge_strategies,
+                        &self.field_ttls,
+                        &self.merge_defaults,
+                        into_metric,
+                        self.audit,
+                        self.provenance_max_values,
+                    );
+                    self.flush_state(state, &discriminant, "ends_when", &mut flushed);
+                }
+            }
         } else {
-            self.push_or_new_reduce_state(event, discriminant)
+            self.reduce_event(event, discriminant, into_metric)
         }
 
-        self.flush_into(output);
+        self.flush_into(&mut flushed, false);
+        self.append_flushed(output, flushed);
     }
 }
 
-impl TaskTransform<Event> for Reduce {
+/// Combines the log events flushed together into a single event with an `events` array.
+///
+/// Metric events are passed through unchanged.
+fn batch_flushed(output: Vec<Event>) -> Vec<Event> {
+    let mut values = Vec::new();
+    let mut metadata: Option<EventMetadata> = None;
+    let mut batched = Vec::new();
+    for event in output {
+        match event {
+            Event::Log(log) => {
+                let (value, event_metadata) = log.into_parts();
+                values.push(value);
+                match metadata.as_mut() {
+                    Some(metadata) => metadata.merge(event_metadata),
+                    None => metadata = Some(event_metadata),
+                }
+            }
+            event => batched.push(event),
+        }
+    }
+    if let Some(metadata) = metadata {
+        let mut log = LogEvent::new_with_metadata(metadata);
+        log.insert("events", Value::Array(values));
+        batched.insert(0, log.into());
+    }
+    batched
+}
+
+impl MultiOutputTaskTransform for Reduce {
     fn transform(
         self: Box<Self>,
-        mut input_rx: Pin<Box<dyn Stream<Item = Event> + Send>>,
-    ) -> Pin<Box<dyn Stream<Item = Event> + Send>>
+        mut input_rx: Pin<Box<dyn Stream<Item = EventArray> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = TransformOutputsBuf> + Send>>
     where
         Self: 'static,
     {
@@ -414,49 +1748,81 @@ impl TaskTransform<Event> for Reduce {
 
         let mut flush_stream = tokio::time::interval(poll_period);
 
-        Box::pin(
-            stream! {
-              loop {
-                let mut output = Vec::new();
-                let done = tokio::select! {
-                    _ = flush_stream.tick() => {
-                      me.flush_into(&mut output);
-                      false
+        Box::pin(stream! {
+          loop {
+            let mut output = me.new_outputs_buf();
+            let done = tokio::select! {
+                _ = flush_stream.tick() => {
+                  let mut flushed = Vec::new();
+                  me.flush_into(&mut flushed, true);
+                  me.flush_rollups_into(&mut flushed);
+                  me.append_flushed(&mut output, flushed);
+                  false
+                }
+                maybe_events = input_rx.next() => {
+                  match maybe_events {
+                    None => {
+                      let mut flushed = Vec::new();
+                      me.flush_all_into(&mut flushed);
+                      me.append_flushed(&mut output, flushed);
+                      true
                     }
-                    maybe_event = input_rx.next() => {
-                      match maybe_event {
-                        None => {
-                          me.flush_all_into(&mut output);
-                          true
-                        }
-                        Some(event) => {
+                    Some(events) => {
+                      for event in events.into_events() {
                           me.transform_one(&mut output, event);
-                          false
-                        }
                       }
+                      false
                     }
-                };
-                yield stream::iter(output.into_iter());
-                if done { break }
-              }
+                  }
+                }
+            };
+            if !output.is_empty() {
+                yield output;
             }
-            .flatten(),
+            if done { break }
+          }
+        })
+    }
+}
+
+/// Runs the transform over a stream of single events, for callers that embed it directly, such as
+/// the `kubernetes_logs` source.
+///
+/// Only the default output is yielded; events sent to the `overflow` output are dropped.
+impl TaskTransform<Event> for Reduce {
+    fn transform(
+        self: Box<Self>,
+        task: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Event> + Send>>
+    where
+        Self: 'static,
+    {
+        let outputs =
+            MultiOutputTaskTransform::transform(self, Box::pin(task.map(EventArray::from)));
+        Box::pin(
+            outputs
+                .flat_map(|mut output| futures::stream::iter(output.take_primary().into_events())),
         )
     }
 }
 
 #[cfg(test)]
 mod test {
+    use chrono::TimeZone;
     use serde_json::json;
     use tokio::sync::mpsc;
     use tokio_stream::wrappers::ReceiverStream;
     use value::Kind;
 
     use super::*;
-    use crate::event::{LogEvent, Value};
+    use crate::event::{
+        BatchNotifier, BatchStatus, EventStatus, Finalizable, LogEvent, Metric, MetricKind,
+        MetricValue, Value,
+    };
     use crate::test_util::components::assert_transform_compliance;
-    use crate::transforms::test::create_topology;
+    use crate::transforms::test::{create_topology, create_topology_with_named_output};
     use lookup::owned_value_path;
+    use vector_core::metric_tags;
 
     #[test]
     fn generate_config() {
@@ -550,6 +1916,45 @@ group_by = [ "request_id" ]
         .await;
     }
 
+    #[test]
+    fn invalid_merge_strategy_paths() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+[merge_strategies]
+'"user agent"' = "retain"
+"]request_id" = "discard"
+"#,
+        )
+        .unwrap();
+
+        let error = Reduce::new(&reduce_config, &Default::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "invalid field paths in merge strategies: `]request_id`"
+        );
+    }
+
+    #[test]
+    fn dedup_cache_size_zero() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+dedup_field = "id"
+dedup_cache_size = 0
+"#,
+        )
+        .unwrap();
+
+        let error = Reduce::new(&reduce_config, &Default::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(error, "`dedup_cache_size` must be greater than zero");
+    }
+
     #[tokio::test]
     async fn reduce_merge_strategies() {
         let reduce_config = toml::from_str::<ReduceConfig>(
@@ -750,4 +2155,1899 @@ merge_strategies.bar = "concat"
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn drop_lone_end() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+drop_lone_end = true
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("lone end");
+            e_1.insert("counter", 1);
+            e_1.insert("request_id", "1");
+            e_1.insert("test_end", "yep");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("counter", 2);
+            e_2.insert("request_id", "2");
+            let metadata_2 = e_2.metadata().clone();
+            tx.send(e_2.into()).await.unwrap();
+
+            let mut e_3 = LogEvent::from("test message 3");
+            e_3.insert("counter", 3);
+            e_3.insert("request_id", "2");
+            e_3.insert("test_end", "yep");
+            tx.send(e_3.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 2".into());
+            assert_eq!(output_1["counter"], Value::from(5));
+            assert_eq!(output_1.metadata(), &metadata_2);
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn field_ttl() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+merge_strategies.errors = "array"
+field_ttl_ms.errors = 1000
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("errors", "old error");
+            e_1.insert("request_id", "1");
+            tx.send(e_1.into()).await.unwrap();
+
+            tokio::time::sleep(Duration::from_millis(1100)).await;
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("errors", "new error");
+            e_2.insert("request_id", "1");
+            tx.send(e_2.into()).await.unwrap();
+
+            let mut e_3 = LogEvent::from("test message 3");
+            e_3.insert("errors", "newest error");
+            e_3.insert("request_id", "1");
+            e_3.insert("test_end", "yep");
+            tx.send(e_3.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 1".into());
+            assert_eq!(
+                output_1["errors"],
+                json!(["new error", "newest error"]).into()
+            );
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[test]
+    fn field_ttl_unsupported_strategy() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+merge_strategies.total = "sum"
+field_ttl_ms.total = 1000
+"#,
+        )
+        .unwrap();
+
+        let error = Reduce::new(&reduce_config, &Default::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "`field_ttl_ms` is only supported for the `array`, `concat`, `concat_newline`, `concat_raw` and `retain` merge strategies, but `total` uses `sum`"
+        );
+
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+field_ttl_ms.status = 1000
+"#,
+        )
+        .unwrap();
+
+        let error = Reduce::new(&reduce_config, &Default::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "`field_ttl_ms` needs a merge strategy for `status`, unless `default_string_strategy` is `last`"
+        );
+    }
+
+    #[tokio::test]
+    async fn reduce_metrics() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "name", "tags" ]
+expire_after_ms = 100
+flush_period_ms = 50
+metric_handling = "round_trip"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (host, value) in [("a", 1.0), ("a", 2.0), ("b", 5.0)] {
+                let counter = Metric::new(
+                    "requests",
+                    MetricKind::Incremental,
+                    MetricValue::Counter { value },
+                )
+                .with_tags(Some(metric_tags!("host" => host)));
+                tx.send(counter.into()).await.unwrap();
+            }
+
+            let mut outputs = vec![
+                out.recv().await.unwrap().into_metric(),
+                out.recv().await.unwrap().into_metric(),
+            ];
+            outputs.sort_by_key(|metric| metric.tag_value("host"));
+
+            assert_eq!(outputs[0].name(), "requests");
+            assert_eq!(outputs[0].kind(), MetricKind::Incremental);
+            assert_eq!(outputs[0].tag_value("host"), Some("a".to_string()));
+            assert_eq!(outputs[0].value(), &MetricValue::Counter { value: 3.0 });
+            assert_eq!(outputs[1].tag_value("host"), Some("b".to_string()));
+            assert_eq!(outputs[1].value(), &MetricValue::Counter { value: 5.0 });
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn acknowledges_events_flushed_at_shutdown() {
+        let reduce_config =
+            toml::from_str::<ReduceConfig>(r#"group_by = [ "request_id" ]"#).unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut receivers = Vec::new();
+            for counter in 1..=5 {
+                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                let mut e = LogEvent::from("test message").with_batch_notifier(&batch);
+                e.insert("request_id", "1");
+                e.insert("counter", counter);
+                tx.send(e.into()).await.unwrap();
+                receivers.push(receiver);
+            }
+
+            drop(tx);
+            let mut output = out.recv().await.unwrap();
+            assert_eq!(output.as_log()["counter"], Value::from(15));
+
+            // the events are only acknowledged once the combined event is
+            for receiver in &mut receivers {
+                assert!(receiver.try_recv().is_err());
+            }
+            output
+                .take_finalizers()
+                .update_status(EventStatus::Delivered);
+            drop(output);
+            for receiver in receivers {
+                assert_eq!(receiver.await, BatchStatus::Delivered);
+            }
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn emit_as_metric() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "service" ]
+emit_as_metric = true
+
+merge_strategies.latency = "max"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (requests, latency) in [(1, 20), (2, 50), (3, 10)] {
+                let mut e = LogEvent::from("test message");
+                e.insert("service", "api");
+                e.insert("requests", requests);
+                e.insert("latency", latency);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let mut outputs = vec![
+                out.recv().await.unwrap().into_metric(),
+                out.recv().await.unwrap().into_metric(),
+            ];
+            outputs.sort_by(|a, b| a.name().cmp(b.name()));
+
+            assert_eq!(outputs[0].name(), "latency");
+            assert_eq!(outputs[0].kind(), MetricKind::Absolute);
+            assert_eq!(outputs[0].tag_value("service"), Some("api".to_string()));
+            assert_eq!(outputs[0].value(), &MetricValue::Gauge { value: 50.0 });
+            assert_eq!(outputs[1].name(), "requests");
+            assert_eq!(outputs[1].kind(), MetricKind::Incremental);
+            assert_eq!(outputs[1].tag_value("service"), Some("api".to_string()));
+            assert_eq!(outputs[1].value(), &MetricValue::Counter { value: 6.0 });
+            assert!(outputs[1].timestamp().is_some());
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn max_group_cardinality() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_group_cardinality = 1
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out, mut overflow) = create_topology_with_named_output(
+                ReceiverStream::new(rx),
+                reduce_config,
+                OVERFLOW_OUTPUT,
+            )
+            .await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("counter", 1);
+            e_1.insert("request_id", "1");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("overflow message 1");
+            e_2.insert("counter", 2);
+            e_2.insert("request_id", "2");
+            tx.send(e_2.into()).await.unwrap();
+
+            let overflow_1 = overflow.recv().await.unwrap().into_log();
+            assert_eq!(overflow_1["message"], "overflow message 1".into());
+            assert_eq!(overflow_1["counter"], Value::from(2));
+
+            let mut e_3 = LogEvent::from("overflow message 2");
+            e_3.insert("counter", 3);
+            e_3.insert("request_id", "2");
+            tx.send(e_3.into()).await.unwrap();
+
+            let overflow_2 = overflow.recv().await.unwrap().into_log();
+            assert_eq!(overflow_2["message"], "overflow message 2".into());
+            assert_eq!(overflow_2["counter"], Value::from(3));
+
+            let mut e_4 = LogEvent::from("test message 2");
+            e_4.insert("counter", 4);
+            e_4.insert("request_id", "1");
+            e_4.insert("test_end", "yep");
+            tx.send(e_4.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 1".into());
+            assert_eq!(output_1["counter"], Value::from(5));
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+            assert_eq!(overflow.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn event_task_transform() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_group_cardinality = 1
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+        let reduce = Reduce::new(&reduce_config, &Default::default()).unwrap();
+
+        let mut e_1 = LogEvent::from("test message 1");
+        e_1.insert("counter", 1);
+        e_1.insert("request_id", "1");
+
+        let mut e_2 = LogEvent::from("overflow message 1");
+        e_2.insert("counter", 2);
+        e_2.insert("request_id", "2");
+
+        let mut e_3 = LogEvent::from("test message 2");
+        e_3.insert("counter", 3);
+        e_3.insert("request_id", "1");
+        e_3.insert("test_end", "yep");
+
+        let input = futures::stream::iter([e_1, e_2, e_3].map(Event::from));
+        let output = TaskTransform::transform(Box::new(reduce), Box::pin(input))
+            .collect::<Vec<_>>()
+            .await;
+
+        // Only the default output is yielded, so the overflow event is not part of the stream.
+        assert_eq!(output.len(), 1);
+        let output_1 = output[0].as_log();
+        assert_eq!(output_1["message"], "test message 1".into());
+        assert_eq!(output_1["counter"], Value::from(4));
+    }
+
+    #[tokio::test]
+    async fn audit() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+audit = true
+
+merge_strategies.counter = "sum"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for counter in 1..=3 {
+                let mut e = LogEvent::from("test message");
+                e.insert("counter", counter);
+                e.insert("request_id", "1");
+                if counter == 3 {
+                    e.insert("test_end", "yep");
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["counter"], Value::from(6));
+            assert_eq!(
+                output_1["reduce.audit.counter"],
+                json!({ "strategy": "sum", "contributions": 3 }).into()
+            );
+            assert_eq!(
+                output_1["reduce.audit.message"],
+                json!({ "strategy": "default", "contributions": 3 }).into()
+            );
+            assert_eq!(
+                output_1["reduce.audit.test_end"],
+                json!({ "strategy": "default", "contributions": 1 }).into()
+            );
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn provenance() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+provenance = true
+provenance_max_values = 3
+
+merge_strategies.counter = "sum"
+merge_strategies.bytes = "sum"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (counter, bytes) in [(1, 10), (5, 20), (2, 30), (7, 40)] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("counter", counter);
+                e.insert("bytes", bytes);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(output["counter"], Value::from(15));
+            assert_eq!(output["bytes"], Value::from(100));
+            assert_eq!(
+                output["reduce.provenance"],
+                json!({ "counter": [1, 5, 2], "bytes": [10, 20, 30] }).into()
+            );
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn count_merge_strategy() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+merge_strategies.user = "count"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for user in [Some("alice"), None, Some("bob"), Some("alice")] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                if let Some(user) = user {
+                    e.insert("user", user);
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(output["user"], Value::from(3));
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn conditional_merge_strategies() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "type" ]
+
+[conditional_merge_strategies]
+field = "type"
+values.metric.value = "sum"
+values.event.value = "retain"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for event_type in ["metric", "event"] {
+                for value in [1, 5, 2] {
+                    let mut e = LogEvent::from("test message");
+                    e.insert("type", event_type);
+                    e.insert("value", value);
+                    tx.send(e.into()).await.unwrap();
+                }
+            }
+
+            drop(tx);
+            let mut values = HashMap::new();
+            for _ in 0..2 {
+                let output = out.recv().await.unwrap().into_log();
+                values.insert(
+                    output["type"].to_string_lossy().into_owned(),
+                    output["value"].clone(),
+                );
+            }
+            assert_eq!(values["metric"], Value::from(8));
+            assert_eq!(values["event"], Value::from(2));
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn conditional_merge_strategies_same_group() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+audit = true
+
+[conditional_merge_strategies]
+field = "type"
+values.metric.value = "sum"
+values.event.value = "retain"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            // both types share a `group_by` value and arrive interleaved
+            for value in [1, 5, 2] {
+                for event_type in ["metric", "event"] {
+                    let mut e = LogEvent::from("test message");
+                    e.insert("request_id", "1");
+                    e.insert("type", event_type);
+                    e.insert("value", value);
+                    tx.send(e.into()).await.unwrap();
+                }
+            }
+
+            drop(tx);
+            let mut outputs = HashMap::new();
+            for _ in 0..2 {
+                let output = out.recv().await.unwrap().into_log();
+                outputs.insert(output["type"].to_string_lossy().into_owned(), output);
+            }
+            assert_eq!(outputs["metric"]["value"], Value::from(8));
+            assert_eq!(
+                outputs["metric"]["reduce.audit.value"],
+                json!({ "strategy": "sum", "contributions": 3 }).into()
+            );
+            assert_eq!(outputs["event"]["value"], Value::from(2));
+            assert_eq!(
+                outputs["event"]["reduce.audit.value"],
+                json!({ "strategy": "retain", "contributions": 3 }).into()
+            );
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn schema() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+schema = true
+audit = true
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for counter in 1..=2 {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("counter", counter);
+                e.insert("ratio", 0.5);
+                e.insert("tags", vec!["a"]);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            let fields = output
+                .as_map()
+                .unwrap()
+                .keys()
+                .filter(|key| key.as_str() != "reduce")
+                .cloned()
+                .collect::<Vec<_>>();
+            let schema = output["reduce.schema"].as_object().unwrap();
+            assert_eq!(schema.keys().cloned().collect::<Vec<_>>(), fields);
+            assert_eq!(schema["counter"], "integer".into());
+            assert_eq!(schema["ratio"], "float".into());
+            assert_eq!(schema["tags"], "array".into());
+            assert_eq!(schema["request_id"], "string".into());
+            assert_eq!(schema["timestamp"], "timestamp".into());
+            assert_eq!(schema["timestamp_end"], "timestamp".into());
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn max_events() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_events = 3
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for counter in 1..=4 {
+                let mut e = LogEvent::from(format!("test message {}", counter));
+                e.insert("counter", counter);
+                e.insert("request_id", "1");
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 1".into());
+            assert_eq!(output_1["counter"], Value::from(6));
+
+            drop(tx);
+            topology.stop().await;
+
+            let output_2 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_2["message"], "test message 4".into());
+            assert_eq!(output_2["counter"], Value::from(4));
+
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[test]
+    fn max_events_flush_metrics() {
+        crate::metrics::init_test();
+
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_events = 2
+"#,
+        )
+        .unwrap();
+        let mut reduce = Reduce::new(&reduce_config, &Default::default()).unwrap();
+
+        let counter_value = |name: &str, reason: Option<&str>| {
+            crate::metrics::Controller::get()
+                .unwrap()
+                .capture_metrics()
+                .into_iter()
+                .filter(|metric| {
+                    metric.name() == name && metric.tag_value("reason").as_deref() == reason
+                })
+                .map(|metric| match metric.value() {
+                    MetricValue::Counter { value } => *value,
+                    value => panic!("unexpected {} metric: {:?}", name, value),
+                })
+                .sum::<f64>()
+        };
+        let stale_before = counter_value("stale_events_flushed_total", None);
+        let max_events_before = counter_value("reduce_states_flushed_total", Some("max_events"));
+
+        let mut output = reduce.new_outputs_buf();
+        for _ in 0..2 {
+            let mut e = LogEvent::from("test message");
+            e.insert("request_id", "1");
+            reduce.transform_one(&mut output, e.into());
+        }
+        assert_eq!(output.len(), 1);
+
+        // a max_events flush isn't a stale event, it is only counted by its reason
+        assert_eq!(
+            counter_value("stale_events_flushed_total", None),
+            stale_before
+        );
+        assert_eq!(
+            counter_value("reduce_states_flushed_total", Some("max_events")),
+            max_events_before + 1.0
+        );
+    }
+
+    #[tokio::test]
+    async fn annotate_flush_reason() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_events = 2
+expire_after_ms = 300
+flush_period_ms = 50
+annotate_flush_reason = true
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for request_id in ["1", "1", "2"] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", request_id);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["request_id"], "1".into());
+            assert_eq!(output_1["reduce.flush_reason"], "max_events".into());
+
+            let output_2 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_2["request_id"], "2".into());
+            assert_eq!(output_2["reduce.flush_reason"], "expired".into());
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[test]
+    fn max_flush_batch() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+expire_after_ms = 0
+max_flush_batch = 2
+"#,
+        )
+        .unwrap();
+        let mut reduce = Reduce::new(&reduce_config, &Default::default()).unwrap();
+
+        let mut output = reduce.new_outputs_buf();
+        for request_id in ["1", "2", "3", "4", "5"] {
+            let mut e = LogEvent::from("test message");
+            e.insert("request_id", request_id);
+            reduce.transform_one(&mut output, e.into());
+            // keep the states' update times distinct, as they are flushed in that order
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(output.is_empty());
+
+        let mut flushed = Vec::new();
+        for _ in 0..3 {
+            let mut output = Vec::new();
+            reduce.flush_into(&mut output, true);
+            flushed.push(
+                output
+                    .into_iter()
+                    .map(|event| {
+                        event.into_log()["request_id"]
+                            .to_string_lossy()
+                            .into_owned()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(flushed, vec![vec!["1", "2"], vec!["3", "4"], vec!["5"]]);
+    }
+
+    #[test]
+    fn states_active() {
+        crate::metrics::init_test();
+
+        let reduce_config =
+            toml::from_str::<ReduceConfig>(r#"group_by = [ "request_id" ]"#).unwrap();
+        let mut reduce = Reduce::new(&reduce_config, &Default::default()).unwrap();
+
+        let mut output = reduce.new_outputs_buf();
+        for request_id in ["1", "2", "2", "3"] {
+            let mut e = LogEvent::from("test message");
+            e.insert("request_id", request_id);
+            reduce.transform_one(&mut output, e.into());
+        }
+        assert!(output.is_empty());
+
+        // the gauges are only emitted on the periodic flush
+        let metrics = crate::metrics::Controller::get().unwrap().capture_metrics();
+        assert!(!metrics
+            .iter()
+            .any(|metric| metric.name() == "reduce_states_active"));
+
+        let mut flushed = Vec::new();
+        reduce.flush_into(&mut flushed, true);
+        assert!(flushed.is_empty());
+
+        let metrics = crate::metrics::Controller::get().unwrap().capture_metrics();
+        let states_active = metrics
+            .iter()
+            .find(|metric| metric.name() == "reduce_states_active")
+            .expect("reduce_states_active should be emitted");
+        assert_eq!(states_active.value(), &MetricValue::Gauge { value: 3.0 });
+
+        let bytes_buffered = metrics
+            .iter()
+            .find(|metric| metric.name() == "reduce_bytes_buffered")
+            .expect("reduce_bytes_buffered should be emitted");
+        assert!(matches!(
+            bytes_buffered.value(),
+            MetricValue::Gauge { value } if *value > 0.0
+        ));
+    }
+
+    #[test]
+    fn state_ages() {
+        crate::metrics::init_test();
+
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+state_age_group_field = "service"
+"#,
+        )
+        .unwrap();
+        let mut reduce = Reduce::new(&reduce_config, &Default::default()).unwrap();
+
+        let mut output = reduce.new_outputs_buf();
+        let mut e = LogEvent::from("test message");
+        e.insert("request_id", "1");
+        e.insert("service", "stuck");
+        reduce.transform_one(&mut output, e.into());
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        let mut e = LogEvent::from("test message");
+        e.insert("request_id", "2");
+        e.insert("service", "stuck");
+        reduce.transform_one(&mut output, e.into());
+        assert!(output.is_empty());
+
+        // the gauges are only emitted on the periodic flush
+        let metrics = crate::metrics::Controller::get().unwrap().capture_metrics();
+        assert!(!metrics
+            .iter()
+            .any(|metric| metric.name() == "reduce_oldest_state_age_seconds"));
+
+        let mut flushed = Vec::new();
+        reduce.flush_into(&mut flushed, true);
+        assert!(flushed.is_empty());
+
+        let metrics = crate::metrics::Controller::get().unwrap().capture_metrics();
+        let oldest = metrics
+            .iter()
+            .find(|metric| {
+                metric.name() == "reduce_oldest_state_age_seconds"
+                    && metric.tag_value("group").as_deref() == Some("stuck")
+            })
+            .expect("reduce_oldest_state_age_seconds should be emitted");
+        assert!(matches!(
+            oldest.value(),
+            MetricValue::Gauge { value } if *value >= 0.1
+        ));
+    }
+
+    /// Generates the schema of `ReduceConfig` and returns the definition of `T` from it.
+    fn schema_definition<T: vector_config::Configurable>() -> serde_json::Value {
+        let schema = vector_config::schema::generate_root_schema::<ReduceConfig>().unwrap();
+        let name = T::referenceable_name().unwrap();
+        serde_json::to_value(&schema.definitions[name]).unwrap()
+    }
+
+    #[test]
+    fn array_merge_by_key_config_round_trip() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+merge_strategies.items = { type = "array_merge_by_key", key = "id" }
+"#,
+        )
+        .unwrap();
+
+        let reduce_config = toml::Value::try_from(&reduce_config)
+            .unwrap()
+            .try_into::<ReduceConfig>()
+            .unwrap();
+        assert!(matches!(
+            &reduce_config.merge_strategies["items"],
+            AnyMergeStrategy::Options(MergeStrategyOptions::ArrayMergeByKey { key }) if key == "id"
+        ));
+    }
+
+    #[test]
+    fn array_merge_by_key_schema() {
+        // `array_merge_by_key` needs a key, so it is only configured through the options form.
+        let definition = schema_definition::<MergeStrategy>();
+        assert!(!definition.to_string().contains("array_merge_by_key"));
+
+        let definition = schema_definition::<MergeStrategyOptions>();
+        let variant = definition["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|variant| variant["properties"]["type"]["const"] == json!("array_merge_by_key"))
+            .expect("the `array_merge_by_key` options should be in the schema");
+        assert_eq!(variant["properties"]["key"]["type"], json!("string"));
+        assert!(variant["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("key")));
+    }
+
+    #[tokio::test]
+    async fn array_merge_by_key() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+merge_strategies.items = { type = "array_merge_by_key", key = "id" }
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("request_id", "1");
+            e_1.insert(
+                "items",
+                Value::from(json!([
+                    { "id": "a", "quantity": 1, "price": 10 },
+                    { "id": "b", "quantity": 2 },
+                ])),
+            );
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("request_id", "1");
+            e_2.insert("test_end", "yep");
+            e_2.insert(
+                "items",
+                Value::from(json!([
+                    { "id": "b", "quantity": 5 },
+                    { "id": "c", "quantity": 3 },
+                ])),
+            );
+            tx.send(e_2.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(
+                output_1["items"],
+                json!([
+                    { "id": "a", "quantity": 1, "price": 10 },
+                    { "id": "b", "quantity": 5 },
+                    { "id": "c", "quantity": 3 },
+                ])
+                .into()
+            );
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn nested_group_by() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ ".http.request_id" ]
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("counter", 1);
+            e_1.insert("http.request_id", "1");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("counter", 2);
+            e_2.insert("http.request_id", "2");
+            tx.send(e_2.into()).await.unwrap();
+
+            let mut e_3 = LogEvent::from("test message 3");
+            e_3.insert("counter", 3);
+            e_3.insert("http.request_id", "1");
+            e_3.insert("test_end", "yep");
+            tx.send(e_3.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 1".into());
+            assert_eq!(output_1["counter"], Value::from(4));
+            assert_eq!(output_1["http.request_id"], "1".into());
+
+            drop(tx);
+            topology.stop().await;
+
+            let output_2 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_2["message"], "test message 2".into());
+            assert_eq!(output_2["counter"], Value::from(2));
+
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[test]
+    fn concat_separator_config_round_trip() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+merge_strategies.message = { type = "concat", separator = ", " }
+"#,
+        )
+        .unwrap();
+
+        let reduce_config = toml::Value::try_from(&reduce_config)
+            .unwrap()
+            .try_into::<ReduceConfig>()
+            .unwrap();
+        assert!(matches!(
+            &reduce_config.merge_strategies["message"],
+            AnyMergeStrategy::Options(MergeStrategyOptions::Concat { separator }) if separator == ", "
+        ));
+    }
+
+    #[test]
+    fn concat_separator_schema() {
+        // named strategies take no options
+        let definition = schema_definition::<MergeStrategy>();
+        assert!(!definition.to_string().contains("separator"));
+
+        let definition = schema_definition::<MergeStrategyOptions>();
+        let variant = definition["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|variant| variant["properties"]["type"]["const"] == json!("concat"))
+            .expect("the `concat` options should be in the schema");
+        assert_eq!(variant["properties"]["separator"]["type"], json!("string"));
+        assert!(variant["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("separator")));
+    }
+
+    #[tokio::test]
+    async fn concat_separator() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+merge_strategies.message = { type = "concat", separator = ", " }
+merge_strategies.tag = "concat"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (message, tag) in [("a", "x"), ("b", "y"), ("c", "z")] {
+                let mut e = LogEvent::from(message);
+                e.insert("tag", tag);
+                e.insert("request_id", "1");
+                if message == "c" {
+                    e.insert("test_end", "yep");
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "a, b, c".into());
+            assert_eq!(output_1["tag"], "x y z".into());
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn batch_output() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+batch_output = true
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (request_id, counter) in [("1", 1), ("2", 2), ("1", 3), ("3", 4)] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", request_id);
+                e.insert("counter", counter);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            let events = output["events"].as_array().unwrap();
+            assert_eq!(events.len(), 3);
+
+            let mut counters = events
+                .iter()
+                .map(|event| {
+                    (
+                        event.get("request_id").unwrap().clone(),
+                        event.get("counter").unwrap().clone(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            counters.sort_by_key(|(request_id, _)| request_id.to_string_lossy());
+            assert_eq!(
+                counters,
+                vec![
+                    ("1".into(), Value::from(4)),
+                    ("2".into(), Value::from(2)),
+                    ("3".into(), Value::from(4)),
+                ]
+            );
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    async fn reduce_with_primary_timestamp(primary_timestamp: &str) -> LogEvent {
+        let reduce_config = toml::from_str::<ReduceConfig>(&format!(
+            r#"
+group_by = [ "request_id" ]
+primary_timestamp = "{}"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+            primary_timestamp
+        ))
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for second in 1..=3u32 {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("timestamp", Utc.ymd(2020, 1, 1).and_hms(0, 0, second));
+                if second == 3 {
+                    e.insert("test_end", "yep");
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output = out.recv().await.unwrap().into_log();
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+            output
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn primary_timestamp() {
+        let output = reduce_with_primary_timestamp("first").await;
+        assert_eq!(
+            output["timestamp"],
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 1).into()
+        );
+        assert_eq!(
+            output["timestamp_end"],
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+        );
+
+        let output = reduce_with_primary_timestamp("last").await;
+        assert_eq!(
+            output["timestamp"],
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+        );
+
+        let before_flush = Utc::now();
+        let output = reduce_with_primary_timestamp("flush_time").await;
+        let timestamp = *output["timestamp"].as_timestamp().unwrap();
+        assert!(timestamp >= before_flush);
+        assert_eq!(
+            output["timestamp_end"],
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn timestamp_end_suffix() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+timestamp_end_suffix = "_last"
+primary_timestamp = "last"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for second in 1..=3u32 {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("timestamp", Utc.ymd(2020, 1, 1).and_hms(0, 0, second));
+                e.insert("timestamp_end", "original");
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(
+                output["timestamp"],
+                Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+            );
+            assert_eq!(
+                output["timestamp_last"],
+                Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+            );
+            assert_eq!(output["timestamp_end"], "original".into());
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+
+        let reduce_config = toml::from_str::<ReduceConfig>("timestamp_end_suffix = ''").unwrap();
+        assert!(Reduce::new(&reduce_config, &Default::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn default_string_strategy_last() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+default_string_strategy = "last"
+
+merge_strategies.first_status = "discard"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (status, counter) in [("pending", 1), ("running", 2), ("done", 3)] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("status", status);
+                e.insert("first_status", status);
+                e.insert("counter", counter);
+                if status == "done" {
+                    e.insert("test_end", "yep");
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(output["status"], "done".into());
+            assert_eq!(output["first_status"], "pending".into());
+            assert_eq!(output["counter"], Value::from(6));
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn default_string_strategy_last_with_field_ttl() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+default_string_strategy = "last"
+
+field_ttl_ms.status = 60000
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for status in ["pending", "running", "done"] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("status", status);
+                if status == "done" {
+                    e.insert("test_end", "yep");
+                }
+                tx.send(e.into()).await.unwrap();
+            }
+
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(output["status"], "done".into());
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn output_fields_are_sorted() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("zulu", "z");
+            e_1.insert("request_id", "1");
+            e_1.insert("nested.yankee", "y");
+            e_1.insert("nested.bravo", "b");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("request_id", "1");
+            e_2.insert("alpha", "a");
+            e_2.insert("test_end", "yep");
+            tx.send(e_2.into()).await.unwrap();
+
+            let output = out.recv().await.unwrap().into_log();
+            let keys = output.keys().unwrap().collect::<Vec<_>>();
+            let mut sorted = keys.clone();
+            sorted.sort();
+            assert_eq!(keys, sorted);
+            assert!(keys.contains(&"alpha".to_string()));
+            assert!(keys.contains(&"nested.yankee".to_string()));
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn expire_after_last_event() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+expire_after_ms = 300
+flush_period_ms = 50
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut quiet = LogEvent::from("quiet message");
+            quiet.insert("request_id", "quiet");
+            quiet.insert("counter", 1);
+            tx.send(quiet.into()).await.unwrap();
+
+            // The steady group receives events well within `expire_after_ms` of each other for
+            // longer than `expire_after_ms`, so it is never considered stale.
+            for _ in 0..6 {
+                let mut steady = LogEvent::from("steady message");
+                steady.insert("request_id", "steady");
+                steady.insert("counter", 1);
+                tx.send(steady.into()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["request_id"], "quiet".into());
+            assert_eq!(output_1["counter"], Value::from(1));
+
+            drop(tx);
+            let output_2 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_2["request_id"], "steady".into());
+            assert_eq!(output_2["counter"], Value::from(6));
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn dedup_field() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+dedup_field = "id"
+
+merge_strategies.id = "array"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for id in ["a", "b", "a", "c", "b"] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("id", id);
+                e.insert("counter", 1);
+                tx.send(e.into()).await.unwrap();
+            }
+            let mut e = LogEvent::from("test message");
+            e.insert("request_id", "1");
+            e.insert("counter", 1);
+            e.insert("test_end", "yep");
+            tx.send(e.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["id"], json!(["a", "b", "c"]).into());
+            assert_eq!(output_1["counter"], Value::from(4));
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    async fn reduce_group_ids() -> HashMap<String, String> {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id", "host" ]
+group_id_field = "group_id"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for request_id in ["1", "2", "1"] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", request_id);
+                e.insert("host", "localhost");
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let mut group_ids = HashMap::new();
+            for _ in 0..2 {
+                let output = out.recv().await.unwrap().into_log();
+                group_ids.insert(
+                    output["request_id"].to_string_lossy().into_owned(),
+                    output["group_id"].to_string_lossy().into_owned(),
+                );
+            }
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+            group_ids
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn group_id_field() {
+        let first = reduce_group_ids().await;
+        let second = reduce_group_ids().await;
+
+        assert_eq!(first.len(), 2);
+        assert_ne!(first["1"], first["2"]);
+        assert_eq!(first["1"].len(), 16);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn group_id_hash_encoding() {
+        let group_by = vec!["request_id".to_owned()];
+        let id = |field: &str, value: Value| {
+            let mut event = LogEvent::default();
+            event.insert(field, value);
+            let discriminant = Discriminant::from_log_event(&event, &[field]);
+            group_id_hash(&[field.to_owned()], &discriminant)
+        };
+
+        // the field names and the types of the values are part of the id
+        assert_ne!(id("request_id", "1".into()), id("host", "1".into()));
+        assert_ne!(id("request_id", "1".into()), id("request_id", 1.into()));
+        assert_eq!(id("request_id", "1".into()), id("request_id", "1".into()));
+
+        // a missing value differs from any present value
+        let missing = Discriminant::from_log_event(&LogEvent::default(), &group_by);
+        assert_ne!(
+            group_id_hash(&group_by, &missing),
+            id("request_id", Value::Null)
+        );
+    }
+
+    #[tokio::test]
+    async fn emit_group_key() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "host", ".http.request_id" ]
+emit_group_key = true
+
+[merge_strategies]
+host = "array"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for _ in 0..2 {
+                let mut e = LogEvent::from("test message");
+                e.insert("host", "localhost");
+                e.insert("http.request_id", "1");
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(output["host"], json!(["localhost", "localhost"]).into());
+            assert_eq!(
+                output["_group"],
+                json!({ "host": "localhost", "http": { "request_id": "1" } }).into()
+            );
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn flush_webhook() {
+        let addr = crate::test_util::next_addr();
+        let (mut rx, trigger, server) = crate::sinks::util::test::build_test_server(addr);
+        tokio::spawn(server);
+
+        let reduce_config = toml::from_str::<ReduceConfig>(&format!(
+            r#"
+group_by = [ "request_id" ]
+flush_webhook.uri = "http://{}/flushed"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+            addr
+        ))
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("request_id", "1");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("request_id", "1");
+            e_2.insert("test_end", "yep");
+            tx.send(e_2.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["message"], "test message 1".into());
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+
+        let (parts, body) = rx.next().await.unwrap();
+        assert_eq!(parts.method, "POST");
+        assert_eq!(parts.uri.path(), "/flushed");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({ "group": { "request_id": "1" }, "event_count": 2 })
+        );
+
+        drop(trigger);
+    }
+
+    #[tokio::test]
+    async fn flush_webhook_group_by_field_with_merge_strategy() {
+        let addr = crate::test_util::next_addr();
+        let (mut rx, trigger, server) = crate::sinks::util::test::build_test_server(addr);
+        tokio::spawn(server);
+
+        let reduce_config = toml::from_str::<ReduceConfig>(&format!(
+            r#"
+group_by = [ "request_id" ]
+flush_webhook.uri = "http://{}/flushed"
+
+merge_strategies.request_id = "concat"
+
+[ends_when]
+  type = "vrl"
+  source = "exists(.test_end)"
+"#,
+            addr
+        ))
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            let mut e_1 = LogEvent::from("test message 1");
+            e_1.insert("request_id", "1");
+            tx.send(e_1.into()).await.unwrap();
+
+            let mut e_2 = LogEvent::from("test message 2");
+            e_2.insert("request_id", "1");
+            e_2.insert("test_end", "yep");
+            tx.send(e_2.into()).await.unwrap();
+
+            let output_1 = out.recv().await.unwrap().into_log();
+            assert_eq!(output_1["request_id"], "1 1".into());
+
+            drop(tx);
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+
+        let (_, body) = rx.next().await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({ "group": { "request_id": "1" }, "event_count": 2 })
+        );
+
+        drop(trigger);
+    }
+
+    #[tokio::test]
+    async fn flush_webhook_drops_when_saturated() {
+        crate::metrics::init_test();
+
+        // never accepted, so the first notification stays in flight
+        let addr = crate::test_util::next_addr();
+        let _listener = std::net::TcpListener::bind(addr).unwrap();
+
+        let config = toml::from_str::<FlushWebhookConfig>(&format!(
+            r#"
+uri = "http://{}/flushed"
+max_in_flight = 1
+"#,
+            addr
+        ))
+        .unwrap();
+        let webhook = FlushWebhook::new(&config, &Default::default()).unwrap();
+
+        let mut e = LogEvent::from("test message");
+        e.insert("request_id", "1");
+        let group_by = vec!["request_id".to_string()];
+        let discriminant = Discriminant::from_log_event(&e, &group_by);
+        webhook.notify(&group_by, &discriminant, 1);
+        webhook.notify(&group_by, &discriminant, 1);
+
+        let metrics = crate::metrics::Controller::get().unwrap().capture_metrics();
+        let dropped = metrics
+            .iter()
+            .find(|metric| metric.name() == "reduce_flush_webhook_dropped_total")
+            .expect("reduce_flush_webhook_dropped_total should be emitted");
+        assert_eq!(dropped.value(), &MetricValue::Counter { value: 1.0 });
+    }
+
+    #[test]
+    fn flush_webhook_max_in_flight_zero() {
+        let config = toml::from_str::<FlushWebhookConfig>(
+            r#"
+uri = "http://localhost:8080/flushed"
+max_in_flight = 0
+"#,
+        )
+        .unwrap();
+        let error = FlushWebhook::new(&config, &Default::default())
+            .err()
+            .expect("zero max_in_flight should be rejected");
+        assert_eq!(
+            error.to_string(),
+            "`flush_webhook.max_in_flight` must be greater than zero"
+        );
+    }
+
+    #[tokio::test]
+    async fn rollup_by() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+rollup_by = [ "service" ]
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (request_id, service, counter) in
+                [("1", "a", 1), ("1", "a", 2), ("2", "a", 5), ("3", "b", 7)]
+            {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", request_id);
+                e.insert("service", service);
+                e.insert("counter", counter);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let mut requests = Vec::new();
+            let mut rollups = Vec::new();
+            for _ in 0..5 {
+                let output = out.recv().await.unwrap().into_log();
+                let counter = output["counter"].clone();
+                match output.get("request_id") {
+                    Some(request_id) => {
+                        requests.push((request_id.to_string_lossy().into_owned(), counter))
+                    }
+                    None => {
+                        rollups.push((output["service"].to_string_lossy().into_owned(), counter))
+                    }
+                }
+            }
+            requests.sort_by(|a, b| a.0.cmp(&b.0));
+            rollups.sort_by(|a, b| a.0.cmp(&b.0));
+
+            assert_eq!(
+                requests,
+                vec![
+                    ("1".into(), Value::from(3)),
+                    ("2".into(), Value::from(5)),
+                    ("3".into(), Value::from(7)),
+                ]
+            );
+            assert_eq!(
+                rollups,
+                vec![("a".into(), Value::from(8)), ("b".into(), Value::from(7))]
+            );
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn reorder_field() {
+        let reduce_config = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+reorder_field = "timestamp"
+reorder_tolerance_ms = 5000
+
+[merge_strategies]
+step = "retain"
+"#,
+        )
+        .unwrap();
+
+        assert_transform_compliance(async move {
+            let (tx, rx) = mpsc::channel(1);
+            let (topology, mut out) = create_topology(ReceiverStream::new(rx), reduce_config).await;
+
+            for (second, step) in [(1, "first"), (3, "third"), (2, "second")] {
+                let mut e = LogEvent::from("test message");
+                e.insert("request_id", "1");
+                e.insert("timestamp", Utc.ymd(2020, 1, 1).and_hms(0, 0, second));
+                e.insert("step", step);
+                tx.send(e.into()).await.unwrap();
+            }
+
+            drop(tx);
+            let output = out.recv().await.unwrap().into_log();
+            assert_eq!(
+                output["timestamp"],
+                Utc.ymd(2020, 1, 1).and_hms(0, 0, 1).into()
+            );
+            assert_eq!(
+                output["timestamp_end"],
+                Utc.ymd(2020, 1, 1).and_hms(0, 0, 3).into()
+            );
+            assert_eq!(output["step"], "third".into());
+
+            topology.stop().await;
+            assert_eq!(out.recv().await, None);
+        })
+        .await;
+    }
 }
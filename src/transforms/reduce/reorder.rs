@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::event::LogEvent;
+
+/// Events of a group held back so they can be combined in event-time order.
+#[derive(Debug)]
+pub(super) struct ReorderBuffer {
+    events: Vec<(DateTime<Utc>, LogEvent, bool)>,
+    latest: Option<DateTime<Utc>>,
+    last_arrival: Instant,
+}
+
+impl ReorderBuffer {
+    pub(super) fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            latest: None,
+            last_arrival: Instant::now(),
+        }
+    }
+
+    pub(super) fn push(&mut self, time: DateTime<Utc>, event: LogEvent, into_metric: bool) {
+        self.latest = Some(self.latest.map_or(time, |latest| latest.max(time)));
+        self.last_arrival = Instant::now();
+        self.events.push((time, event, into_metric));
+    }
+
+    /// Whether no event has been added for at least `tolerance`.
+    pub(super) fn is_idle(&self, tolerance: Duration) -> bool {
+        self.last_arrival.elapsed() >= tolerance
+    }
+
+    /// Removes the events that are more than `tolerance` behind the latest event time seen, in
+    /// event-time order.
+    pub(super) fn release(&mut self, tolerance: Duration) -> Vec<(LogEvent, bool)> {
+        let tolerance =
+            chrono::Duration::from_std(tolerance).unwrap_or_else(|_| chrono::Duration::max_value());
+        let watermark = match self
+            .latest
+            .and_then(|latest| latest.checked_sub_signed(tolerance))
+        {
+            Some(watermark) => watermark,
+            None => return Vec::new(),
+        };
+
+        self.events.sort_by_key(|(time, _, _)| *time);
+        let ready = self
+            .events
+            .partition_point(|(time, _, _)| *time <= watermark);
+        self.events
+            .drain(..ready)
+            .map(|(_, event, into_metric)| (event, into_metric))
+            .collect()
+    }
+
+    /// Removes all events, in event-time order.
+    pub(super) fn into_sorted(mut self) -> Vec<(LogEvent, bool)> {
+        self.events.sort_by_key(|(time, _, _)| *time);
+        self.events
+            .into_iter()
+            .map(|(_, event, into_metric)| (event, into_metric))
+            .collect()
+    }
+}
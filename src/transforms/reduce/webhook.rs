@@ -0,0 +1,129 @@
+use std::{sync::Arc, time::Duration};
+
+use http::{header::CONTENT_TYPE, Request, Uri};
+use hyper::Body;
+use serde_json::{json, Map};
+use serde_with::serde_as;
+use tokio::sync::Semaphore;
+use vector_config::configurable_component;
+
+use crate::{
+    config::ProxyConfig,
+    event::{discriminant::Discriminant, Value},
+    http::HttpClient,
+    internal_events::{ReduceFlushWebhookDropped, ReduceFlushWebhookError},
+};
+
+/// Configuration for notifying an HTTP endpoint when combined events are flushed.
+#[serde_as]
+#[configurable_component]
+#[derive(Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FlushWebhookConfig {
+    /// The URI to send a summary of each flushed combined event to.
+    ///
+    /// The summary is sent as a JSON object in a `POST` request, with the `group_by` values of
+    /// the combined event under `group` and the number of events combined into it under
+    /// `event_count`.
+    #[configurable(metadata(docs::examples = "http://localhost:8080/reduce-flushed"))]
+    pub uri: String,
+
+    /// The maximum number of notifications that can be in flight at once.
+    ///
+    /// Notifications for flushes that happen while this many requests are outstanding are dropped
+    /// rather than queued, so a slow endpoint can't hold up the transform or grow its memory use.
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+
+    /// The maximum time to wait for the endpoint to respond to a notification, in seconds.
+    #[serde(default = "default_timeout_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub timeout_secs: Duration,
+}
+
+const fn default_max_in_flight() -> usize {
+    100
+}
+
+const fn default_timeout_secs() -> Duration {
+    Duration::from_secs(10)
+}
+
+/// Sends flush summaries to the configured endpoint without blocking the transform.
+pub(super) struct FlushWebhook {
+    client: HttpClient,
+    uri: Uri,
+    in_flight: Arc<Semaphore>,
+    timeout: Duration,
+}
+
+impl FlushWebhook {
+    pub(super) fn new(config: &FlushWebhookConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        if config.max_in_flight == 0 {
+            return Err("`flush_webhook.max_in_flight` must be greater than zero".into());
+        }
+        let uri = config.uri.parse::<Uri>()?;
+        let client = HttpClient::new(None, proxy)?;
+        Ok(Self {
+            client,
+            uri,
+            in_flight: Arc::new(Semaphore::new(config.max_in_flight)),
+            timeout: config.timeout_secs,
+        })
+    }
+
+    /// Sends the summary of a flushed combined event in the background.
+    ///
+    /// The group is taken from the discriminant of the flushed state rather than the combined
+    /// event, whose `group_by` fields may have been changed or removed by a merge strategy.
+    ///
+    /// The summary is dropped if `max_in_flight` notifications are already outstanding.
+    pub(super) fn notify(
+        &self,
+        group_by: &[String],
+        discriminant: &Discriminant,
+        event_count: usize,
+    ) {
+        let permit = match Arc::clone(&self.in_flight).try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                emit!(ReduceFlushWebhookDropped);
+                return;
+            }
+        };
+
+        let group = group_by
+            .iter()
+            .zip(discriminant.values())
+            .map(|(field, value)| {
+                let value = value.clone().unwrap_or(Value::Null);
+                (
+                    field.clone(),
+                    serde_json::to_value(value).unwrap_or_default(),
+                )
+            })
+            .collect::<Map<_, _>>();
+        let body = json!({ "group": group, "event_count": event_count }).to_string();
+
+        let request = Request::post(&self.uri)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("building the webhook request should not fail");
+        let response = tokio::time::timeout(self.timeout, self.client.send(request));
+        tokio::spawn(async move {
+            match response.await {
+                Ok(Ok(response)) if response.status().is_success() => {}
+                Ok(Ok(response)) => emit!(ReduceFlushWebhookError {
+                    error: format!("unexpected response status: {}", response.status()),
+                }),
+                Ok(Err(error)) => emit!(ReduceFlushWebhookError {
+                    error: error.to_string(),
+                }),
+                Err(_) => emit!(ReduceFlushWebhookError {
+                    error: "request timed out".to_string(),
+                }),
+            }
+            drop(permit);
+        });
+    }
+}
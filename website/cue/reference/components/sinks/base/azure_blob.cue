@@ -1,6 +1,24 @@
 package metadata
 
 base: components: sinks: azure_blob: configuration: {
+	access_tier: {
+		description: """
+			The access tier to upload blobs to.
+
+			If unset, blobs are uploaded to the default access tier of the storage account.
+			"""
+		required: false
+		type: string: enum: {
+			archive: """
+				Optimized for data that is rarely accessed and stored for at least 180 days.
+
+				Blobs in this tier are offline, and must be rehydrated to another tier before they can be
+				read.
+				"""
+			cool: "Optimized for data that is accessed infrequently and stored for at least 30 days."
+			hot:  "Optimized for data that is accessed frequently."
+		}
+	}
 	acknowledgements: {
 		description: """
 			Controls how acknowledgements are handled for this sink.
@@ -141,6 +159,28 @@ base: components: sinks: azure_blob: configuration: {
 		required:    true
 		type: string: {}
 	}
+	customer_provided_key: {
+		description: """
+			A base64-encoded AES-256 key to encrypt uploaded blobs with.
+
+			The key is sent with each upload and is not stored by Azure Blob Storage, so the same key
+			is needed to read the blobs back. Cannot be used together with `encryption_scope`.
+			"""
+		required: false
+		type: string: {}
+	}
+	dlq_path: {
+		description: """
+			A local directory to write uploads that permanently failed to.
+
+			When set, the body of an upload that fails with an error that is not retried, such as a
+			`403 Forbidden` response, is written to a new file in this directory named after its blob
+			followed by a UUID. Otherwise, the body of such an upload is dropped. Either way, its events
+			are reported as rejected rather than delivered.
+			"""
+		required: false
+		type: string: examples: ["/var/lib/vector/azure_blob_dlq"]
+	}
 	encoding: {
 		description: "Configures how events are encoded into raw bytes."
 		required:    true
@@ -257,6 +297,16 @@ base: components: sinks: azure_blob: configuration: {
 			}
 		}
 	}
+	encryption_scope: {
+		description: """
+			The name of the encryption scope to encrypt uploaded blobs with.
+
+			The encryption scope must already exist in the storage account. Cannot be used together
+			with `customer_provided_key`.
+			"""
+		required: false
+		type: string: {}
+	}
 	framing: {
 		description: "Framing configuration."
 		required:    false
@@ -287,6 +337,49 @@ base: components: sinks: azure_blob: configuration: {
 			}
 		}
 	}
+	healthcheck_write_probe: {
+		description: """
+			Whether the healthcheck also checks that blobs can be written to the container.
+
+			When enabled, the healthcheck uploads an empty blob to the container and deletes it again,
+			so that missing write permissions are reported at startup instead of on the first upload.
+			This also requires permission to delete blobs. It is disabled by default, as it writes to
+			the container. Cannot be used together with the `archive` access tier.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	json: {
+		description: """
+			Formatting options for blobs encoded with the `json` codec.
+
+			Cannot be used with any other codec, and `pretty` cannot be used with newline-delimited
+			framing.
+			"""
+		required: false
+		type: object: options: {
+			escape_non_ascii: {
+				description: """
+					Whether to escape non-ASCII characters.
+
+					When enabled, every character outside of the ASCII range is written as a `\\uXXXX` escape
+					sequence (using a surrogate pair where needed), producing output that is pure ASCII.
+					"""
+				required: false
+				type: bool: default: false
+			}
+			pretty: {
+				description: """
+					Whether to use pretty JSON formatting.
+
+					When disabled (the default), events are written compactly with no insignificant whitespace.
+					As pretty output spans multiple lines, it can't be combined with newline-delimited framing.
+					"""
+				required: false
+				type: bool: default: false
+			}
+		}
+	}
 	request: {
 		description: """
 			Middleware settings for outbound requests.
@@ -431,4 +524,14 @@ base: components: sinks: azure_blob: configuration: {
 		required: false
 		type: string: {}
 	}
+	verify_checksum: {
+		description: """
+			Whether or not to send an MD5 checksum of the blob contents with each upload.
+
+			When enabled, Azure Blob Storage verifies the uploaded content against the checksum and rejects
+			the upload if they do not match. Rejected uploads are retried.
+			"""
+		required: false
+		type: bool: default: false
+	}
 }
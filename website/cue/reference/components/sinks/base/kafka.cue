@@ -196,6 +196,9 @@ base: components: sinks: kafka: configuration: {
 		description: """
 			The log field name to use for the Kafka headers.
 
+			The field must hold an object, whose keys and values become the headers. Values that are not
+			strings are converted to strings, and null values are skipped.
+
 			If omitted, no headers will be written.
 			"""
 		required: false
@@ -207,10 +210,14 @@ base: components: sinks: kafka: configuration: {
 
 			If the field does not exist in the log or in tags, a blank value will be used. If unspecified, the key is not sent.
 
+			A template such as `{{ user_id }}` can also be given to build the key from the event. If
+			the template fails to render, for example because a field it references is missing, the
+			key is not sent.
+
 			Kafka uses a hash of the key to choose the partition or uses round-robin if the record has no key.
 			"""
 		required: false
-		type: string: {}
+		type: string: syntax: "template"
 	}
 	librdkafka_options: {
 		description: """
@@ -226,11 +233,67 @@ base: components: sinks: kafka: configuration: {
 			type: string: {}
 		}
 	}
+	max_headers: {
+		description: """
+			The maximum number of headers to write per message.
+
+			Headers beyond this count are dropped, and the number dropped is reported in the
+			`kafka_headers_dropped_total` metric. This should not exceed the limits of the brokers,
+			which otherwise reject the message. If unspecified, there is no limit.
+			"""
+		required: false
+		type: uint: {}
+	}
+	max_headers_bytes: {
+		description: """
+			The maximum total size of the headers to write per message, in bytes.
+
+			The size of a header is the length of its key plus the length of its value. Headers that
+			would exceed this size are dropped, and the number dropped is reported in the
+			`kafka_headers_dropped_total` metric. If unspecified, there is no limit.
+			"""
+		required: false
+		type: uint: {}
+	}
 	message_timeout_ms: {
 		description: "Local message timeout, in milliseconds."
 		required:    false
 		type: uint: default: 300000
 	}
+	request: {
+		description: """
+			Settings for retrying failed sends.
+
+			Sends are delivered at least once: a send that timed out with a `MessageTimedOut` error is
+			retried, even though the broker may have written it, which can produce duplicate messages.
+			"""
+		required: false
+		type: object: options: {
+			retry_attempts: {
+				description: """
+					The maximum number of retries to make for a failed send.
+
+					Set to `0` to disable retries.
+					"""
+				required: false
+				type: uint: default: 5
+			}
+			retry_initial_backoff_secs: {
+				description: """
+					The amount of time, in seconds, to wait before attempting the first retry for a failed send.
+
+					After the first retry has failed, the fibonacci sequence will be used to select future backoffs.
+					"""
+				required: false
+				type: uint: default: 1
+			}
+			retry_max_duration_secs: {
+				description: "The maximum amount of time, in seconds, to wait between retries."
+				required:    false
+				type: uint: default: 3600
+			}
+		}
+	}
 	sasl: {
 		description: "Configuration for SASL authentication when interacting with Kafka."
 		required:    false
@@ -267,6 +330,28 @@ base: components: sinks: kafka: configuration: {
 			}
 		}
 	}
+	send_timeout_ms: {
+		description: """
+			The time, in milliseconds, to wait for a send to be acknowledged before failing it.
+
+			A send that times out fails with a `MessageTimedOut` error, freeing its slot for other
+			sends. If unspecified, sends wait for an acknowledgement indefinitely.
+
+			Timed out sends are retried according to the `request` settings.
+			"""
+		required: false
+		type: uint: {}
+	}
+	slow_send_threshold_ms: {
+		description: """
+			The time, in milliseconds, after which a send that has not been acknowledged is considered slow.
+
+			Sends that take longer than this are counted in the `kafka_slow_sends_total` metric. If
+			unspecified, sends are never considered slow.
+			"""
+		required: false
+		type: uint: {}
+	}
 	socket_timeout_ms: {
 		description: "Default timeout, in milliseconds, for network requests."
 		required:    false
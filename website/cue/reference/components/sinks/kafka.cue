@@ -166,5 +166,9 @@ components: sinks: kafka: {
 		kafka_produced_messages_bytes_total: components.sources.internal_metrics.output.metrics.kafka_produced_messages_bytes_total
 		kafka_consumed_messages_total:       components.sources.internal_metrics.output.metrics.kafka_consumed_messages_total
 		kafka_consumed_messages_bytes_total: components.sources.internal_metrics.output.metrics.kafka_consumed_messages_bytes_total
+		kafka_headers_dropped_total:         components.sources.internal_metrics.output.metrics.kafka_headers_dropped_total
+		kafka_delivered_messages_total:      components.sources.internal_metrics.output.metrics.kafka_delivered_messages_total
+		kafka_send_latency_seconds:          components.sources.internal_metrics.output.metrics.kafka_send_latency_seconds
+		kafka_slow_sends_total:              components.sources.internal_metrics.output.metrics.kafka_slow_sends_total
 	}
 }
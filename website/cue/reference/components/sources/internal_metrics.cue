@@ -705,6 +705,39 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		kafka_headers_dropped_total: {
+			description:       "Total number of message headers dropped for exceeding `max_headers` or `max_headers_bytes`."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		kafka_delivered_messages_total: {
+			description:       "Total number of messages acknowledged by Kafka, by topic and partition."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				topic: {
+					description: "The Kafka topic the message was delivered to."
+					required:    true
+				}
+				partition: {
+					description: "The Kafka partition the message was delivered to."
+					required:    true
+				}
+			}
+		}
+		kafka_send_latency_seconds: {
+			description:       "The time between submitting a message to Kafka and it being acknowledged."
+			type:              "histogram"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		kafka_slow_sends_total: {
+			description:       "Total number of messages that took longer than `slow_send_threshold_ms` to be acknowledged by Kafka."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		kafka_produced_messages_total: {
 			description:       "Total number of messages transmitted (produced) to Kafka brokers."
 			type:              "counter"
@@ -1053,6 +1086,63 @@ components: sources: internal_metrics: {
 				}
 			}
 		}
+		group_cardinality_exceeded_total: {
+			description:       "The number of events that were not reduced because the group cardinality limit was reached."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		reduce_bytes_buffered: {
+			description:       "The estimated size, in bytes, of the events held in in-flight reduce states, updated every `flush_period_ms`."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		reduce_flush_webhook_dropped_total: {
+			description:       "The number of `flush_webhook` notifications dropped because `max_in_flight` requests were already outstanding."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		reduce_oldest_state_age_seconds: {
+			description:       "The time since the oldest in-flight reduce state was started, updated every `flush_period_ms`."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				group: _reduce_state_group
+			}
+		}
+		reduce_state_max_idle_seconds: {
+			description:       "The longest time an in-flight reduce state has gone without receiving an event, updated every `flush_period_ms`."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				group: _reduce_state_group
+			}
+		}
+		reduce_states_active: {
+			description:       "The number of in-flight reduce states, one per distinct group, updated every `flush_period_ms`."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		reduce_states_flushed_total: {
+			description:       "The number of reduce states flushed by the periodic flush or at shutdown, by reason."
+			type:              "counter"
+			default_namespace: "vector"
+
+			tags: _component_tags & {
+				reason: {
+					description: "The reason the state was flushed."
+					required:    true
+					enum: {
+						"expired":    "The state was not updated within `expire_after_ms`."
+						"max_events": "The state reached `max_events`."
+						"shutdown":   "The transform was shut down while the state was open."
+					}
+				}
+			}
+		}
 		stale_events_flushed_total: {
 			description:       "The number of stale events that Vector has flushed."
 			type:              "counter"
@@ -1287,6 +1377,10 @@ components: sources: internal_metrics: {
 			description: "The specific output of the component."
 			required:    false
 		}
+		_reduce_state_group: {
+			description: "The value of the `state_age_group_field` field of the reduce states."
+			required:    false
+		}
 		_stage: {
 			description: "The stage within the component at which the error occurred."
 			required:    true
@@ -1,6 +1,212 @@
 package metadata
 
 base: components: transforms: reduce: configuration: {
+	annotate_flush_reason: {
+		description: """
+			Whether to record why each combined event was flushed.
+
+			When enabled, every combined event gets a `reduce.flush_reason` field holding the cause of
+			the flush: `expired` when no event arrived for the group within `expire_after_ms`,
+			`max_events` when the group reached `max_events`, `ends_when` or `starts_when` when an event
+			matched the respective condition, or `shutdown` when the transform stopped. This helps to
+			debug combined events that are flushed earlier than expected.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	audit: {
+		description: """
+			Whether to record how each field of a combined event was merged.
+
+			When enabled, every combined event gets a `reduce.audit` object with an entry per field. Each
+			entry holds the merge `strategy` applied to the field (`default` when none is configured) and
+			the number of values that contributed to it as `contributions`. This is intended for
+			debugging and is disabled by default.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	batch_output: {
+		description: """
+			Whether to emit the combined events flushed at the same time as a single event.
+
+			When enabled, instead of emitting one event per group, the combined events flushed together
+			are emitted as one event holding them in its `events` array. This reduces per-event
+			overhead for destinations that ingest events in batches. Combined events converted back to
+			metrics are not batched.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	conditional_merge_strategies: {
+		description: """
+			Merge strategies selected by the value of a field.
+
+			For example, to sum `value` for events with a `type` of `metric` but keep the last `value` for
+			events with a `type` of `event`:
+
+			```toml
+			[conditional_merge_strategies]
+			field = "type"
+			values.metric.value = "sum"
+			values.event.value = "retain"
+			```
+			"""
+		required: false
+		type: object: options: {
+			field: {
+				description: """
+					The field whose value selects the merge strategies for an event.
+
+					Events are also grouped by this field, as if it were listed in `group_by`, so events with
+					different values are never combined together and every combined event is merged with a
+					single set of strategies.
+					"""
+				required: true
+				type: string: examples: ["type"]
+			}
+			values: {
+				description: """
+					A map of values of `field` to the merge strategies used for events with that value.
+
+					These take precedence over `merge_strategies` for the fields they name. Events whose value
+					is not listed, or that do not have the field, use `merge_strategies`.
+					"""
+				required: true
+				type: object: options: "*": {
+					description: "The merge strategies for events with this value."
+					required:    true
+					type: object: options: "*": {
+						description: "Strategies for merging events."
+						required:    true
+						type: string: enum: {
+							array:          "Append each value to an array."
+							average: """
+								Keep the mean of all numeric values seen.
+
+								The result is always a floating-point number.
+								"""
+							concat:         "Concatenate each string value, delimited with a space."
+							concat_newline: "Concatenate each string value, delimited with a newline."
+							concat_raw:     "Concatenate each string, without a delimiter."
+							count: """
+								Count the values seen.
+
+								The result is the number of events that had the field, whatever its values were.
+								"""
+							discard:        "Discard all but the first value found."
+							first_last: """
+								Keep the first and the last value seen, as the `first` and `last` fields of an object.
+
+								This works with values of any type, and is useful to track how a field changed over the
+								combined events.
+								"""
+							flat_unique:    "Create a flattened array of all unique values."
+							longest_array:  "Keep the longest array seen."
+							max:            "Keep the maximum numeric value seen."
+							min:            "Keep the minimum numeric value seen."
+							retain: """
+								Discard all but the last value found.
+
+								Works as a way to coalesce by not retaining `null`.
+								"""
+							set: """
+								Append each value to an array, unless an equal value is already present.
+
+								Values are kept in the order they were first seen. Objects and arrays are compared by
+								structural equality and are not flattened.
+								"""
+							shortest_array: "Keep the shortest array seen."
+							std_dev: """
+								Keep the population standard deviation of all numeric values seen.
+
+								The standard deviation is computed as values are received, without keeping them. A single
+								value has a standard deviation of `0`. The result is always a floating-point number.
+								"""
+							sum: "Sum all numeric values."
+							variance: """
+								Keep the population variance of all numeric values seen.
+
+								The variance is computed as values are received, without keeping them. A single value has a
+								variance of `0`. The result is always a floating-point number.
+								"""
+						}
+					}
+				}
+			}
+		}
+	}
+	dedup_cache_size: {
+		description: """
+			The size of the filter used to detect duplicate events, in bytes.
+
+			Each combined event gets its own filter of this size. Seen values are tracked with a Bloom
+			filter, which uses bounded memory but can report false positives, where an event with a
+			value that has not been seen before is dropped as a duplicate. The filter is sized for one
+			distinct value per 10 bits, so the default of 1024 bytes holds about 800 values with a
+			false positive rate of about 1%. The rate grows as a combined event gets more distinct
+			values than that.
+			"""
+		required: false
+		type: uint: default: 1024
+	}
+	dedup_field: {
+		description: """
+			The field used to detect duplicate events within a combined event.
+
+			When set, an event whose value for this field has already been combined into the same
+			combined event is dropped. Events without this field are never considered duplicates.
+			"""
+		required: false
+		type: string: {}
+	}
+	default_string_strategy: {
+		description: "How string fields without a configured merge strategy are combined."
+		required:    false
+		type: string: {
+			default: "first"
+			enum: {
+				first: "The first value of the field is kept, subsequent values are discarded."
+				last:  "The last value of the field is kept, previous values are discarded."
+			}
+		}
+	}
+	drop_lone_end: {
+		description: """
+			Whether to drop a final event that arrives when no transaction is open for its group.
+
+			By default, an event matching `ends_when` with no previous events in its group is emitted
+			on its own as a single-event transaction. When enabled, such lone final events are dropped
+			instead.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	emit_as_metric: {
+		description: """
+			Whether to emit the numeric fields of combined events as metrics.
+
+			When enabled, each numeric top-level field of a combined event is emitted as a metric named
+			after the field, tagged with the values of the `group_by` fields, instead of the combined
+			event itself. Fields combined with the `sum` or `count` strategies, including numeric fields
+			without a configured strategy, become incremental counters, and other numeric fields become
+			absolute gauges. Combined events without numeric fields are emitted as logs.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	emit_group_key: {
+		description: """
+			Whether to write the `group_by` values of each combined event to a `_group` object.
+
+			When enabled, every combined event gets a `_group` object holding the value of each
+			`group_by` field that was present on the events of its group, at the same path as in the
+			events. This identifies the group of a combined event even when a merge strategy changed or
+			discarded the `group_by` fields themselves.
+			"""
+		required: false
+		type: bool: default: false
+	}
 	ends_when: {
 		description: """
 			A condition used to distinguish the final event of a transaction.
@@ -22,6 +228,27 @@ base: components: transforms: reduce: configuration: {
 			unit:    "milliseconds"
 		}
 	}
+	field_ttl_ms: {
+		description: """
+			A map of field names to a time-to-live, in milliseconds, for the values merged into them.
+
+			Values older than the TTL are dropped from the field while the transaction stays open, so
+			only values received within the TTL contribute to the combined event. This is useful for
+			keeping a rolling window of recent values, such as with the `array` strategy. If every value
+			of a field has expired, the field is omitted from the combined event.
+
+			Each value is kept until it expires, so a TTL is only supported for fields merged with the
+			`array`, `concat`, `concat_newline`, `concat_raw` or `retain` strategies. A field without a
+			merge strategy can only have a TTL when `default_string_strategy` is `last`, in which case
+			its values must be strings.
+			"""
+		required: false
+		type: object: options: "*": {
+			description: "The TTL for the field."
+			required:    true
+			type: uint: {}
+		}
+	}
 	flush_period_ms: {
 		description: "The interval to check for and flush any expired events, in milliseconds."
 		required:    false
@@ -30,6 +257,41 @@ base: components: transforms: reduce: configuration: {
 			unit:    "milliseconds"
 		}
 	}
+	flush_webhook: {
+		description: "Configuration for notifying an HTTP endpoint when combined events are flushed."
+		required:    false
+		type: object: options: {
+			max_in_flight: {
+				description: """
+					The maximum number of notifications that can be in flight at once.
+
+					Notifications for flushes that happen while this many requests are outstanding are dropped
+					rather than queued, so a slow endpoint can't hold up the transform or grow its memory use.
+					"""
+				required: false
+				type: uint: default: 100
+			}
+			timeout_secs: {
+				description: "The maximum time to wait for the endpoint to respond to a notification, in seconds."
+				required:    false
+				type: uint: {
+					default: 10
+					unit:    "seconds"
+				}
+			}
+			uri: {
+				description: """
+					The URI to send a summary of each flushed combined event to.
+
+					The summary is sent as a JSON object in a `POST` request, with the `group_by` values of
+					the combined event under `group` and the number of events combined into it under
+					`event_count`.
+					"""
+				required: true
+				type: string: examples: ["http://localhost:8080/reduce-flushed"]
+			}
+		}
+	}
 	group_by: {
 		description: """
 			An ordered list of fields by which to group events.
@@ -40,6 +302,8 @@ base: components: transforms: reduce: configuration: {
 
 			For example, if `group_by = ["host", "region"]`, then all incoming events that have the same
 			host and region will be grouped together before being reduced.
+
+			Fields may be given as paths to group by nested values, such as `http.request_id`.
 			"""
 		required: false
 		type: array: {
@@ -47,6 +311,57 @@ base: components: transforms: reduce: configuration: {
 			items: type: string: examples: ["request_id", "user_id", "transaction_id"]
 		}
 	}
+	group_id_field: {
+		description: """
+			The field to write an id for the group of a combined event to.
+
+			The id is a hex digest of the `group_by` fields and values, so combined events for the same
+			group get the same id across flushes, restarts, instances, and releases. This is useful as a
+			compact key for joining with other systems. If unset, no id is written.
+			"""
+		required: false
+		type: string: {}
+	}
+	max_events: {
+		description: """
+			The maximum number of events to combine into a single combined event.
+
+			When a group has received this many events, its combined event is flushed immediately, and
+			subsequent events for the group start a new combined event.
+
+			By default, there is no limit.
+			"""
+		required: false
+		type: uint: {}
+	}
+	max_flush_batch: {
+		description: """
+			The maximum number of expired combined events to flush every `flush_period_ms`.
+
+			When set, combined events are only checked for expiry every `flush_period_ms` rather than
+			also after every event, and at most this many are flushed at a time, least recently
+			updated first. The rest are flushed on the following flush periods. This smooths the load
+			on downstream components when many groups expire at once. Combined events flushed because
+			of `ends_when`, `starts_when`, or `max_events` are still flushed immediately.
+
+			By default, there is no limit.
+			"""
+		required: false
+		type: uint: {}
+	}
+	max_group_cardinality: {
+		description: """
+			The maximum number of distinct groups the transform will track.
+
+			Once this many distinct `group_by` values have been seen, events for any new group are not
+			reduced and are instead sent unchanged to the `overflow` output. Groups seen before the
+			limit was reached continue to be reduced, even after their transactions have been flushed.
+
+			By default, there is no limit.
+			"""
+		required: false
+		type: uint: {}
+	}
 	merge_strategies: {
 		description: """
 			A map of field names to custom merge strategies.
@@ -56,10 +371,16 @@ base: components: transforms: reduce: configuration: {
 
 			The default behavior is as follows:
 
-			- The first value of a string field is kept, subsequent values are discarded.
+			- The first value of a string field is kept, subsequent values are discarded. This can be
+			  changed with `default_string_strategy`.
 			- For timestamp fields the first is kept and a new field `[field-name]_end` is added with
-			  the last received timestamp value.
+			  the last received timestamp value. The `_end` suffix can be changed with
+			  `timestamp_end_suffix`.
 			- Numeric values are summed.
+
+			Strategies that take options are given as an object with the strategy name under `type`,
+			such as `{ type = "concat", separator = ", " }` or
+			`{ type = "array_merge_by_key", key = "id" }`.
 			"""
 		required: false
 		type: object: options: "*": {
@@ -67,10 +388,26 @@ base: components: transforms: reduce: configuration: {
 			required:    true
 			type: string: enum: {
 				array:          "Append each value to an array."
+				average: """
+					Keep the mean of all numeric values seen.
+
+					The result is always a floating-point number.
+					"""
 				concat:         "Concatenate each string value, delimited with a space."
 				concat_newline: "Concatenate each string value, delimited with a newline."
 				concat_raw:     "Concatenate each string, without a delimiter."
+				count: """
+					Count the values seen.
+
+					The result is the number of events that had the field, whatever its values were.
+					"""
 				discard:        "Discard all but the first value found."
+				first_last: """
+					Keep the first and the last value seen, as the `first` and `last` fields of an object.
+
+					This works with values of any type, and is useful to track how a field changed over the
+					combined events.
+					"""
 				flat_unique:    "Create a flattened array of all unique values."
 				longest_array:  "Keep the longest array seen."
 				max:            "Keep the maximum numeric value seen."
@@ -80,11 +417,153 @@ base: components: transforms: reduce: configuration: {
 
 					Works as a way to coalesce by not retaining `null`.
 					"""
+				set: """
+					Append each value to an array, unless an equal value is already present.
+
+					Values are kept in the order they were first seen. Objects and arrays are compared by
+					structural equality and are not flattened.
+					"""
 				shortest_array: "Keep the shortest array seen."
-				sum:            "Sum all numeric values."
+				std_dev: """
+					Keep the population standard deviation of all numeric values seen.
+
+					The standard deviation is computed as values are received, without keeping them. A single
+					value has a standard deviation of `0`. The result is always a floating-point number.
+					"""
+				sum: "Sum all numeric values."
+				variance: """
+					Keep the population variance of all numeric values seen.
+
+					The variance is computed as values are received, without keeping them. A single value has a
+					variance of `0`. The result is always a floating-point number.
+					"""
 			}
 		}
 	}
+	metric_handling: {
+		description: """
+			How metric events are handled.
+
+			By default, only log events are accepted. Counter and gauge metrics can be reduced by
+			converting them to log events first, for example to sum counters grouped by `name` and
+			`tags`. Other metric types are dropped.
+			"""
+		required: false
+		type: string: {
+			default: "none"
+			enum: {
+				none: "Metric events are not accepted."
+				round_trip: """
+					Counter and gauge metrics are converted to log events before being reduced, and converted
+					back to metrics when the combined event is flushed.
+
+					If a combined event can no longer be represented as a metric, it is emitted as a log.
+					"""
+				to_log: """
+					Counter and gauge metrics are converted to log events before being reduced.
+
+					Each metric becomes a log event with the fields `name`, `namespace`, `tags`, `kind`,
+					`type`, `value`, and `timestamp`, and combined events are emitted as logs.
+					"""
+			}
+		}
+	}
+	primary_timestamp: {
+		description: "Which timestamp becomes the primary timestamp of a combined event."
+		required:    false
+		type: string: {
+			default: "first"
+			enum: {
+				first:      "The timestamp of the first event is kept."
+				flush_time: "The time at which the combined event is flushed is used."
+				last: """
+					The timestamp of the last event is used.
+
+					This only applies when the timestamp field is combined with the default behavior, which
+					also records the last timestamp in the `[field-name]_end` field.
+					"""
+			}
+		}
+	}
+	provenance: {
+		description: """
+			Whether to record the values that contributed to each summed field.
+
+			When enabled, every combined event gets a `reduce.provenance` object with an array per field
+			merged with the `sum` strategy, holding the values that were added together in the order
+			they were received. This allows a bad contribution to be corrected downstream. At most
+			`provenance_max_values` values are recorded per field.
+			"""
+		required: false
+		type: bool: default: false
+	}
+	provenance_max_values: {
+		description: """
+			The maximum number of values to record per field when `provenance` is enabled.
+
+			Values received after this limit is reached are still summed, but are not recorded.
+			"""
+		required: false
+		type: uint: default: 100
+	}
+	reorder_field: {
+		description: """
+			The timestamp field used to combine the events of a group in event-time order.
+
+			When set, events are held back until they are more than `reorder_tolerance_ms` behind the
+			latest timestamp seen for their group, and are combined in timestamp order. This keeps
+			merge strategies that depend on order, such as `retain` or the `[field-name]_end`
+			timestamp, correct when events arrive slightly out of order. Held back events are also
+			combined once no event has arrived for their group for `reorder_tolerance_ms`, and events
+			that match `starts_when` or `ends_when` or have no timestamp in this field are never held
+			back.
+			"""
+		required: false
+		type: string: examples: ["timestamp"]
+	}
+	reorder_tolerance_ms: {
+		description: """
+			How far out of order events may arrive within a group, in milliseconds.
+
+			Only used when `reorder_field` is set.
+			"""
+		required: false
+		type: uint: {
+			default: 1000
+			unit:    "milliseconds"
+		}
+	}
+	rollup_by: {
+		description: """
+			An ordered list of fields by which to roll up combined events.
+
+			When specified, every flushed combined event is also added to a rollup for its values of
+			these fields, which sums the numeric top-level fields of all combined events in the rollup.
+			Rollups are emitted alongside the combined events every `flush_period_ms`, and hold the
+			rollup fields and the summed numeric fields.
+
+			For example, with `group_by = ["request_id"]` and `rollup_by = ["service"]`, an event is
+			emitted per request, and periodically an event per service with the totals of its requests.
+			"""
+		required: false
+		type: array: {
+			default: []
+			items: type: string: examples: ["service"]
+		}
+	}
+	schema: {
+		description: """
+			Whether to describe the fields of each combined event.
+
+			When enabled, every combined event gets a `reduce.schema` object with an entry per
+			top-level field holding the kind of its value, one of `string`, `integer`, `float`,
+			`boolean`, `timestamp`, `map`, `array`, or `null`. This helps downstream consumers parse
+			combined events whose fields vary. The `reduce` field itself is not described, and combined
+			events converted back to metrics are not given a description.
+			"""
+		required: false
+		type: bool: default: false
+	}
 	starts_when: {
 		description: """
 			A condition used to distinguish the first event of a transaction.
@@ -95,4 +574,33 @@ base: components: transforms: reduce: configuration: {
 		required: false
 		type: condition: {}
 	}
+	state_age_group_field: {
+		description: """
+			The field used to tag the state age metrics with a coarse group.
+
+			On every flush, the `reduce_state_max_idle_seconds` and `reduce_oldest_state_age_seconds`
+			gauges report how long the open combined events have gone without a new event, and how
+			long the oldest of them has been open. When set, these gauges are reported separately for
+			each value of this field, taken from the first event of each combined event, in a `group`
+			tag. This field should have few distinct values, such as a service name, to keep the
+			number of reported series small.
+			"""
+		required: false
+		type: string: examples: ["service"]
+	}
+	timestamp_end_suffix: {
+		description: """
+			The suffix of the field holding the last value of a timestamp field.
+
+			Timestamp fields without a configured merge strategy keep their first value, and their last
+			value is written to a field named after the timestamp field followed by this suffix. Change
+			it when events already have fields ending with `_end` that would otherwise be overwritten.
+			The suffix must not be empty.
+			"""
+		required: false
+		type: string: {
+			default: "_end"
+			examples: ["_last"]
+		}
+	}
 }
@@ -144,7 +144,28 @@ components: transforms: reduce: {
 		},
 	]
 
+	outputs: [
+		components._default_output,
+		{
+			name: "overflow"
+			description: """
+				This transform also implements an additional `overflow` output when
+				`max_group_cardinality` is set. Once the limit is reached, events for any new
+				group are sent unchanged to the `overflow` output instead of being reduced. For a
+				transform component named `foo`, this output can be accessed by specifying
+				`foo.overflow` as the input to another component.
+				"""
+		},
+	]
+
 	telemetry: metrics: {
-		stale_events_flushed_total: components.sources.internal_metrics.output.metrics.stale_events_flushed_total
+		group_cardinality_exceeded_total:   components.sources.internal_metrics.output.metrics.group_cardinality_exceeded_total
+		reduce_bytes_buffered:              components.sources.internal_metrics.output.metrics.reduce_bytes_buffered
+		reduce_flush_webhook_dropped_total: components.sources.internal_metrics.output.metrics.reduce_flush_webhook_dropped_total
+		reduce_oldest_state_age_seconds:    components.sources.internal_metrics.output.metrics.reduce_oldest_state_age_seconds
+		reduce_state_max_idle_seconds:      components.sources.internal_metrics.output.metrics.reduce_state_max_idle_seconds
+		reduce_states_active:               components.sources.internal_metrics.output.metrics.reduce_states_active
+		reduce_states_flushed_total:        components.sources.internal_metrics.output.metrics.reduce_states_flushed_total
+		stale_events_flushed_total:         components.sources.internal_metrics.output.metrics.stale_events_flushed_total
 	}
 }